@@ -0,0 +1,51 @@
+# English, the fallback language, every key must be defined here.
+
+-language-name = English
+
+window-title = Breakout in Rust/Bevy
+
+hud-score = Score:{" "}
+hud-high-score = High score:{" "}
+hud-time = Time:{" "}
+hud-level = Level{" "}
+hud-high-score-value = { $score } (level { $level })
+
+level-banner = Level { $number }
+
+game-over = Game over
+versus-winner = Player { $number } wins!
+
+settings-on = On
+settings-off = Off
+settings-press-key = Press a key...
+settings-language = Language: { $value }
+settings-volume = Volume: { $value }%
+settings-mute = Mute: { $value }
+settings-key-left = Move left: { $value }
+settings-key-right = Move right: { $value }
+settings-key-launch = Launch: { $value }
+settings-key-aim-left = Aim left: { $value }
+settings-key-aim-right = Aim right: { $value }
+settings-launch-mode = Launch direction: { $value }
+settings-launch-random = Random
+settings-launch-aimed = Aimed
+settings-launch-paddle-motion = Paddle motion
+settings-paddle-catch = Catch the ball: { $value }
+settings-sensitivity = Paddle sensitivity: { $value }
+settings-theme = Theme: { $value }
+settings-color-blind-palette = Color-blind palette: { $value }
+settings-brick-glyphs = Brick glyphs: { $value }
+settings-high-contrast = High contrast: { $value }
+settings-ui-scale = UI scale: { $value }
+settings-reduced-motion = Reduced motion: { $value }
+settings-framerate = Frame rate cap: { $value }
+settings-fullscreen = Fullscreen: { $value }
+
+editor-help-paint = Left click: paint, right click: erase, K: hit points, C: color
+editor-help-keys = [ ]: columns, - =: rows, Delete: clear, Ctrl+Z/Y: undo/redo
+editor-help-file = Ctrl+S: save, Ctrl+O: load, Enter: test-play
+editor-status = Bricks: { $count }, size: { $cols }x{ $rows }, hit points: { $hit_points }, color: { $color }
+editor-color-theme = theme
+editor-saved = Saved in { $path }
+editor-loaded = Loaded from { $path }
+editor-error = Error: { $error }
@@ -0,0 +1,51 @@
+# Français
+
+-language-name = Français
+
+window-title = Casse-briques en Rust/Bevy
+
+hud-score = Score :{" "}
+hud-high-score = Meilleur score :{" "}
+hud-time = Temps :{" "}
+hud-level = Niveau{" "}
+hud-high-score-value = { $score } (niveau { $level })
+
+level-banner = Niveau { $number }
+
+game-over = Partie terminée
+versus-winner = Le joueur { $number } gagne !
+
+settings-on = Oui
+settings-off = Non
+settings-press-key = Appuyez sur une touche...
+settings-language = Langue : { $value }
+settings-volume = Volume : { $value } %
+settings-mute = Muet : { $value }
+settings-key-left = Aller à gauche : { $value }
+settings-key-right = Aller à droite : { $value }
+settings-key-launch = Lancer : { $value }
+settings-key-aim-left = Viser à gauche : { $value }
+settings-key-aim-right = Viser à droite : { $value }
+settings-launch-mode = Direction du lancer : { $value }
+settings-launch-random = Aléatoire
+settings-launch-aimed = Visée
+settings-launch-paddle-motion = Mouvement de la raquette
+settings-paddle-catch = Attraper la balle : { $value }
+settings-sensitivity = Sensibilité de la raquette : { $value }
+settings-theme = Thème : { $value }
+settings-color-blind-palette = Palette pour daltoniens : { $value }
+settings-brick-glyphs = Symboles sur les briques : { $value }
+settings-high-contrast = Contraste élevé : { $value }
+settings-ui-scale = Taille de l’interface : { $value }
+settings-reduced-motion = Animations réduites : { $value }
+settings-framerate = Images par seconde max : { $value }
+settings-fullscreen = Plein écran : { $value }
+
+editor-help-paint = Clic gauche : peindre, clic droit : effacer, K : points de vie, C : couleur
+editor-help-keys = [ ] : colonnes, - = : lignes, Suppr : tout effacer, Ctrl+Z/Y : annuler/rétablir
+editor-help-file = Ctrl+S : enregistrer, Ctrl+O : charger, Entrée : tester
+editor-status = Briques : { $count }, taille : { $cols }x{ $rows }, points de vie : { $hit_points }, couleur : { $color }
+editor-color-theme = thème
+editor-saved = Enregistré dans { $path }
+editor-loaded = Chargé depuis { $path }
+editor-error = Erreur : { $error }
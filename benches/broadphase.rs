@@ -0,0 +1,123 @@
+//! Headless benchmarks of the ball’s collisions, with and without the broadphase grid.
+//!
+//! Bricks are laid out in a square area, balls are spread over the same area.
+//! Run with `cargo bench --bench broadphase`.
+
+use bevy::{prelude::*, sprite::collide_aabb::collide};
+use breakout_game::resources::{Broadphase, GameConfig};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const BALLS: usize = 100;
+const BRICK_COUNTS: [usize; 3] = [100, 1_000, 10_000];
+
+/// Bricks in a square area, as entities, centers and sizes.
+fn bricks(count: usize, cfg: &GameConfig) -> Vec<(Entity, Vec2, Vec2)> {
+    let cols = (count as f32).sqrt().ceil() as usize;
+    (0..count)
+        .map(|index| {
+            let cell = Vec2::new((index % cols) as f32, (index / cols) as f32);
+            (
+                Entity::from_raw(index as u32),
+                cell * cfg.brick_size,
+                cfg.brick_size,
+            )
+        })
+        .collect()
+}
+
+/// Balls randomly spread over the area of `brick_count` bricks, as centers and sizes.
+fn balls(brick_count: usize, cfg: &GameConfig) -> Vec<(Vec2, Vec2)> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let side = (brick_count as f32).sqrt().ceil();
+    let area = side * cfg.brick_size;
+    let size = Vec2::splat(cfg.ball_radius * 2.);
+    (0..BALLS)
+        .map(|_| {
+            let center = Vec2::new(rng.gen_range(0. ..area.x), rng.gen_range(0. ..area.y));
+            (center, size)
+        })
+        .collect()
+}
+
+fn grid(bricks: &[(Entity, Vec2, Vec2)], cfg: &GameConfig) -> Broadphase {
+    let mut broadphase = Broadphase::new(cfg.broadphase_cell_size);
+    for &(entity, center, size) in bricks {
+        broadphase.insert(entity, center, size);
+    }
+    broadphase
+}
+
+fn collisions(c: &mut Criterion) {
+    let cfg = GameConfig::default();
+    let mut group = c.benchmark_group("collisions");
+
+    for count in BRICK_COUNTS {
+        let bricks = bricks(count, &cfg);
+        let balls = balls(count, &cfg);
+        let broadphase = grid(&bricks, &cfg);
+
+        group.bench_with_input(BenchmarkId::new("broadphase", count), &count, |b, _| {
+            let mut candidates = Vec::new();
+            b.iter(|| {
+                let mut hits = 0;
+                for &(ball_center, ball_size) in balls.iter() {
+                    broadphase.query(ball_center, ball_size, &mut candidates);
+                    for &entity in candidates.iter() {
+                        let (_, center, size) = bricks[entity.index() as usize];
+                        let collision =
+                            collide(center.extend(0.), size, ball_center.extend(0.), ball_size);
+                        hits += collision.is_some() as usize;
+                    }
+                }
+                black_box(hits)
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("all_bricks", count), &count, |b, _| {
+            b.iter(|| {
+                let mut hits = 0;
+                for &(ball_center, ball_size) in balls.iter() {
+                    for &(_, center, size) in bricks.iter() {
+                        let collision =
+                            collide(center.extend(0.), size, ball_center.extend(0.), ball_size);
+                        hits += collision.is_some() as usize;
+                    }
+                }
+                black_box(hits)
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn updates(c: &mut Criterion) {
+    let cfg = GameConfig::default();
+    let mut group = c.benchmark_group("broadphase_updates");
+
+    for count in BRICK_COUNTS {
+        let bricks = bricks(count, &cfg);
+
+        group.bench_with_input(BenchmarkId::new("rebuild", count), &count, |b, _| {
+            b.iter(|| black_box(grid(&bricks, &cfg)));
+        });
+
+        // Like a level of moving bricks, all bricks move a pixel and a few change cell
+        group.bench_with_input(BenchmarkId::new("move_all", count), &count, |b, _| {
+            let mut broadphase = grid(&bricks, &cfg);
+            let mut offset = 0.;
+            b.iter(|| {
+                offset = (offset + 1.) % cfg.broadphase_cell_size;
+                for &(entity, center, size) in bricks.iter() {
+                    broadphase.insert(entity, center + Vec2::new(offset, 0.), size);
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, collisions, updates);
+criterion_main!(benches);
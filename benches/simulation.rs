@@ -0,0 +1,161 @@
+//! Headless benchmarks of a whole frame of the game, and of the collision routines.
+//!
+//! The game runs with [`HeadlessPlugins`], balls are thrown as soon as they are reset
+//! and the game restarts on game over, so each frame of a scenario is alike.
+//! Run with `cargo bench --bench simulation`.
+
+use bevy::{asset::AssetPlugin, input::InputPlugin, prelude::*, sprite::collide_aabb::collide};
+use breakout_game::{
+    ball::bounce_ball,
+    components::{Ball, BallLaunch, BallSpeed, BoundingBox, Player, StationaryBall, Velocity},
+    paddle::stop_paddle_at_wall,
+    resources::GameConfig,
+    HeadlessPlugins,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// Configuration of the benchmarks, the balls don’t wait to be thrown.
+fn config() -> GameConfig {
+    GameConfig {
+        ball_auto_launch_delay: Some(0.),
+        ..Default::default()
+    }
+}
+
+/// Headless game with `balls` more balls, after its first frame.
+fn headless_app(cfg: GameConfig, balls: usize) -> App {
+    let ball_size = Vec2::splat(cfg.ball_radius * 2.);
+
+    let mut app = App::new();
+    app.insert_resource(cfg)
+        .add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin::default())
+        .add_plugin(InputPlugin)
+        .add_plugins(HeadlessPlugins);
+    app.update();
+
+    for _ in 0..balls {
+        app.world
+            .spawn(TransformBundle::default())
+            .insert(Ball)
+            .insert(StationaryBall)
+            .insert(Player(0))
+            .insert(BoundingBox(ball_size))
+            .insert(Velocity(Vec2::ZERO))
+            .insert(BallSpeed::default())
+            .insert(BallLaunch {
+                angle: 0.,
+                timer: Some(Timer::from_seconds(0., TimerMode::Once)),
+            });
+    }
+    app.update();
+
+    app
+}
+
+fn frames(c: &mut Criterion) {
+    let mut group = c.benchmark_group("frame");
+
+    let scenarios = [
+        ("default", config(), 0),
+        // Every cell of a wide area has a brick
+        (
+            "dense_grid",
+            GameConfig {
+                window_width: 1600.,
+                window_height: 1200.,
+                brick_area_cols: 38,
+                brick_area_rows: 30,
+                brick_spawn_probability: 1.,
+                ..config()
+            },
+            0,
+        ),
+        ("many_balls", config(), 99),
+        // Ten times the usual speed
+        (
+            "high_speed",
+            GameConfig {
+                ball_base_speed: config().ball_base_speed * 10.,
+                ..config()
+            },
+            9,
+        ),
+    ];
+
+    for (name, cfg, balls) in scenarios {
+        let mut app = headless_app(cfg, balls);
+        group.bench_function(name, |b| b.iter(|| app.update()));
+    }
+
+    group.finish();
+}
+
+fn collisions(c: &mut Criterion) {
+    let cfg = config();
+    let ball_size = Vec2::splat(cfg.ball_radius * 2.);
+    let brick_translation = Vec3::new(0., 100., cfg.brick_z);
+    let wall_size = Vec2::new(cfg.wall_thickness * 2., cfg.window_height);
+    let wall_translation = Vec3::new(cfg.window_width / 2., 0., cfg.wall_z);
+
+    c.bench_function("bounce_ball", |b| {
+        b.iter(|| {
+            // Below the brick, going up
+            let mut ball_translation = Vec3::new(5., 100. - cfg.brick_size.y / 2., cfg.ball_z);
+            let mut ball_velocity = Vec2::new(100., 500.);
+            let collision = collide(
+                brick_translation,
+                cfg.brick_size,
+                black_box(ball_translation),
+                ball_size,
+            );
+            if let Some(collision) = collision {
+                bounce_ball(
+                    &collision,
+                    &mut ball_translation,
+                    &mut ball_velocity,
+                    ball_size,
+                    brick_translation,
+                    cfg.brick_size,
+                );
+            }
+            black_box((ball_translation, ball_velocity))
+        });
+    });
+
+    c.bench_function("stop_paddle_at_wall", |b| {
+        b.iter(|| {
+            // Half in the right wall
+            let mut paddle_translation =
+                Vec3::new(cfg.window_width / 2. - cfg.paddle_size.x / 4., 0., 0.);
+            let mut paddle_velocity = Vec2::new(cfg.paddle_base_speed, 0.);
+            stop_paddle_at_wall(
+                black_box(&mut paddle_translation),
+                &mut paddle_velocity,
+                cfg.paddle_size,
+                wall_translation,
+                wall_size,
+            );
+            black_box((paddle_translation, paddle_velocity))
+        });
+    });
+
+    // Far from the walls, the most frequent case
+    c.bench_function("stop_paddle_at_wall_free", |b| {
+        b.iter(|| {
+            let mut paddle_translation = Vec3::ZERO;
+            let mut paddle_velocity = Vec2::new(cfg.paddle_base_speed, 0.);
+            stop_paddle_at_wall(
+                black_box(&mut paddle_translation),
+                &mut paddle_velocity,
+                cfg.paddle_size,
+                wall_translation,
+                wall_size,
+            );
+            black_box((paddle_translation, paddle_velocity))
+        });
+    });
+}
+
+criterion_group!(benches, frames, collisions);
+criterion_main!(benches);
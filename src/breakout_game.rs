@@ -1,19 +1,77 @@
 //! This module contains the game as plugins.
 
+#[cfg(feature = "debug")]
+use self::debug::DebugPlugin;
 use self::{
-    ball::BallPlugin, brick::BrickPlugin, common::CommonPlugin, game_assets::GameAssetsPlugin,
-    paddle::PaddlePlugin, score::ScorePlugin, wall::WallPlugin,
+    accessibility::AccessibilityPlugin,
+    audio::AudioPlugin,
+    ball::BallPlugin,
+    brick::BrickPlugin,
+    broadphase::BroadphasePlugin,
+    common::CommonPlugin,
+    controls::ControlsPlugin,
+    editor::EditorPlugin,
+    events::{
+        BallContact, BallLost, BrickDestroyed, Desync, GameOver, LevelCleared, PaddleHit, WallHit,
+    },
+    game_assets::GameAssetsPlugin,
+    hud::HudPlugin,
+    level::LevelPlugin,
+    localization::LocalizationPlugin,
+    motion::MotionPlugin,
+    net::NetPlugin,
+    paddle::PaddlePlugin,
+    particles::ParticlePlugin,
+    resources::{GameConfig, Paused, SimulationClock, Stalled},
+    save::SavePlugin,
+    score::ScorePlugin,
+    session::SessionPlugin,
+    settings::SettingsPlugin,
+    shake::ShakePlugin,
+    skin::SkinPlugin,
+    telemetry::TelemetryPlugin,
+    theme::ThemePlugin,
+    trail::TrailPlugin,
+    wall::WallPlugin,
 };
-use bevy::{app::PluginGroupBuilder, prelude::*};
+use bevy::{
+    app::PluginGroupBuilder, ecs::schedule::ShouldRun, input::InputSystem, prelude::*,
+    time::TimeUpdateStrategy,
+};
+use std::time::Duration;
 
+pub mod accessibility;
+pub mod audio;
 pub mod ball;
 pub mod brick;
+pub mod broadphase;
 pub mod common;
 pub mod components;
+pub mod controls;
+#[cfg(feature = "debug")]
+pub mod debug;
+pub mod editor;
+pub mod events;
 mod game_assets;
+pub mod generator;
+pub mod hud;
+pub mod level;
+pub mod localization;
+pub mod motion;
+pub mod net;
 pub mod paddle;
+pub mod particles;
+pub mod preferences;
 pub mod resources;
+pub mod save;
 pub mod score;
+pub mod session;
+pub mod settings;
+pub mod shake;
+pub mod skin;
+pub mod telemetry;
+pub mod theme;
+pub mod trail;
 pub mod wall;
 
 /// Minimal plugins for the game
@@ -25,6 +83,7 @@ impl PluginGroup for MinimalPlugins {
         PluginGroupBuilder::start::<Self>()
             .add(CorePlugin)
             .add(GameAssetsPlugin)
+            .add(ThemePlugin)
     }
 }
 
@@ -34,18 +93,78 @@ impl PluginGroup for MinimalPlugins {
 ///  - [`bevy::prelude::DefaultPlugins`]
 ///  - [`bevy::prelude::Camera2dBundle`]
 ///  - [`bevy_prototype_lyon::plugin::ShapePlugin`]
+///
+/// [`bevy::prelude::DefaultPlugins`] must have the `wav` feature of Bevy enabled.
 pub struct DefaultPlugins;
 
 impl PluginGroup for DefaultPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        let group = PluginGroupBuilder::start::<Self>()
+            .add(CorePlugin)
+            .add(GameAssetsPlugin)
+            .add(ThemePlugin)
+            .add(AccessibilityPlugin)
+            .add(AudioPlugin)
+            .add(BallPlugin)
+            .add(BrickPlugin)
+            .add(BroadphasePlugin)
+            .add(CommonPlugin)
+            .add(ControlsPlugin)
+            .add(EditorPlugin)
+            .add(HudPlugin)
+            .add(LevelPlugin)
+            .add(LocalizationPlugin)
+            .add(MotionPlugin)
+            .add(NetPlugin)
+            .add(PaddlePlugin)
+            .add(ParticlePlugin)
+            .add(SavePlugin)
+            .add(ScorePlugin)
+            .add(SessionPlugin)
+            .add(SettingsPlugin)
+            .add(ShakePlugin)
+            .add(SkinPlugin)
+            .add(TelemetryPlugin)
+            .add(TrailPlugin)
+            .add(WallPlugin);
+
+        // Release builds don’t carry the debug overlay
+        #[cfg(feature = "debug")]
+        let group = group.add(DebugPlugin);
+        group
+    }
+}
+
+/// Gameplay plugins only, without rendering, audio nor UI, to run the game headless.
+///
+/// Need the following to be added in Bevy’s app to works
+///  - [`bevy::prelude::MinimalPlugins`]
+///  - [`bevy::asset::AssetPlugin`]
+///  - [`bevy::input::InputPlugin`]
+///
+/// The game is advanced frame by frame with [`App::update`], like in the benchmarks.
+/// Each frame lasts a tick of the simulation, unless the app updates the time itself.
+pub struct HeadlessPlugins;
+
+impl PluginGroup for HeadlessPlugins {
     fn build(self) -> PluginGroupBuilder {
         PluginGroupBuilder::start::<Self>()
             .add(CorePlugin)
+            .add(HeadlessTimePlugin)
             .add(GameAssetsPlugin)
+            .add(ThemePlugin)
             .add(BallPlugin)
             .add(BrickPlugin)
+            .add(BroadphasePlugin)
             .add(CommonPlugin)
+            .add(ControlsPlugin)
+            .add(LevelPlugin)
+            .add(MotionPlugin)
+            .add(NetPlugin)
             .add(PaddlePlugin)
             .add(ScorePlugin)
+            .add(SessionPlugin)
+            .add(TelemetryPlugin)
             .add(WallPlugin)
     }
 }
@@ -55,21 +174,113 @@ struct CorePlugin;
 
 impl Plugin for CorePlugin {
     fn build(&self, app: &mut App) {
-        app.add_stage_before(CoreStage::Update, GameStage::Init, SystemStage::parallel())
-            .add_stage_before(CoreStage::Update, GameStage::Input, SystemStage::parallel())
-            .add_stage_before(CoreStage::Update, GameStage::Move, SystemStage::parallel())
+        app.insert_resource(Paused::default())
+            .insert_resource(Stalled::default())
+            .insert_resource(SimulationClock::default())
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                simulation_clock_system.after(InputSystem),
+            )
+            .add_stage_before(
+                CoreStage::Update,
+                GameStage::Init,
+                SystemStage::parallel().with_run_criteria(simulation_tick),
+            )
+            .add_stage_before(
+                CoreStage::Update,
+                GameStage::Input,
+                SystemStage::parallel().with_run_criteria(simulation_tick),
+            )
+            .add_stage_before(
+                CoreStage::Update,
+                GameStage::Move,
+                SystemStage::parallel().with_run_criteria(simulation_tick),
+            )
             .add_stage_before(
                 CoreStage::Update,
                 GameStage::Paddle,
-                SystemStage::parallel(),
+                SystemStage::parallel().with_run_criteria(simulation_tick),
+            )
+            .add_stage_before(
+                CoreStage::Update,
+                GameStage::Ball,
+                SystemStage::parallel().with_run_criteria(simulation_tick),
+            )
+            .add_stage_before(
+                CoreStage::Update,
+                GameStage::Events,
+                SystemStage::parallel().with_run_criteria(simulation_tick),
             )
-            .add_stage_before(CoreStage::Update, GameStage::Ball, SystemStage::parallel())
-            .add_stage_before(CoreStage::Update, GameStage::Ui, SystemStage::parallel());
+            .add_stage_before(CoreStage::Update, GameStage::Ui, SystemStage::parallel())
+            .add_event::<BrickDestroyed>()
+            .add_event::<BallLost>()
+            .add_event::<PaddleHit>()
+            .add_event::<WallHit>()
+            .add_event::<BallContact>()
+            .add_event::<LevelCleared>()
+            .add_event::<GameOver>()
+            .add_event::<Desync>();
+    }
+}
+
+/// Fixed time step of the frames in headless games.
+struct HeadlessTimePlugin;
+
+impl Plugin for HeadlessTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(headless_time_system);
+    }
+}
+
+fn headless_time_system(mut time_update: ResMut<TimeUpdateStrategy>, cfg: Res<GameConfig>) {
+    if let TimeUpdateStrategy::Automatic = *time_update {
+        *time_update = TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(cfg.time_step()));
+    }
+}
+
+/// Decide if the gameplay stages run a tick of the simulation in the current frame.
+///
+/// Ordered after the systems pausing the game in [`CoreStage::PreUpdate`] stage.
+fn simulation_clock_system(
+    time: Res<Time>,
+    mut clock: ResMut<SimulationClock>,
+    paused: Res<Paused>,
+    stalled: Res<Stalled>,
+    cfg: Res<GameConfig>,
+) {
+    let step = cfg.time_step();
+
+    if paused.0 || stalled.0 {
+        // Ticks as soon as the game goes on
+        clock.accumulator = step;
+        clock.tick = false;
+        return;
+    }
+
+    // Ticks in the middle of the step, frames as fast as the ticks never skip one because of jitter
+    clock.accumulator += time.delta_seconds();
+    clock.tick = clock.accumulator >= step / 2.;
+    if clock.tick {
+        clock.accumulator -= step;
+    }
+    // Slow frames aren’t caught up on, a single tick is run per frame
+    clock.accumulator = clock.accumulator.clamp(-step / 2., step);
+}
+
+/// Run criteria of the gameplay stages, only run on the ticks of the simulation.
+fn simulation_tick(clock: Res<SimulationClock>) -> ShouldRun {
+    if clock.tick {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
     }
 }
 
 /// Stages of the game, executed in the order of declaration and before [`CoreStage::Update`].
-#[derive(StageLabel)]
+///
+/// All the stages but [`GameStage::Ui`] only run on the ticks of the simulation,
+/// never while the game is [`Paused`] or [`Stalled`], see [`SimulationClock`].
+#[derive(StageLabel, Clone, Copy)]
 enum GameStage {
     /// Initialize things that need to be reinitialized multiple times.
     /// Bricks are spawned and balls are reset at this stage.
@@ -89,6 +300,9 @@ enum GameStage {
     /// Should be run after Paddle to get an up to date velocity for collision.
     Ball,
 
+    /// Listeners of the gameplay [`events`] (score, etc.).
+    Events,
+
     /// UI related systems.
     Ui,
 }
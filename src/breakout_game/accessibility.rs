@@ -0,0 +1,143 @@
+//! Accessibility related stuff.
+//!
+//! Color-blind safe palette and high-contrast mode are applied by [`super::theme`],
+//! reduced motion is checked by [`super::particles`] and [`super::shake`],
+//! UI scale is applied by [`super::hud`].
+//!
+//!  - Draw glyphs over bricks to tell their kind and remaining hits without colors,
+//!    redrawn when the hit points change, at [`GameStage::Ui`] stage.
+
+use super::{
+    components::{Brick, BrickGlyph, BrickHitPoints, BrickKind},
+    resources::{ActiveTheme, GameConfig},
+    GameStage,
+};
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+/// Number of different glyphs, stronger bricks share the last one.
+const GLYPH_COUNT: usize = 6;
+
+/// Accessibility logic as a Bevy’s plugin.
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(GameStage::Ui, brick_glyph_system);
+    }
+}
+
+/// Build the glyph at `index` fitting in a rectangle of the given `size`.
+fn glyph_path(index: usize, size: Vec2) -> Path {
+    let half = Vec2::new(size.y, size.y) * 0.3;
+    let mut builder = PathBuilder::new();
+
+    match index % GLYPH_COUNT {
+        // Horizontal line
+        0 => {
+            builder.move_to(Vec2::new(-half.x, 0.));
+            builder.line_to(Vec2::new(half.x, 0.));
+        }
+        // Vertical line
+        1 => {
+            builder.move_to(Vec2::new(0., -half.y));
+            builder.line_to(Vec2::new(0., half.y));
+        }
+        // Plus
+        2 => {
+            builder.move_to(Vec2::new(-half.x, 0.));
+            builder.line_to(Vec2::new(half.x, 0.));
+            builder.move_to(Vec2::new(0., -half.y));
+            builder.line_to(Vec2::new(0., half.y));
+        }
+        // Cross
+        3 => {
+            builder.move_to(-half);
+            builder.line_to(half);
+            builder.move_to(Vec2::new(-half.x, half.y));
+            builder.line_to(Vec2::new(half.x, -half.y));
+        }
+        // Square
+        4 => {
+            builder.move_to(-half);
+            builder.line_to(Vec2::new(half.x, -half.y));
+            builder.line_to(half);
+            builder.line_to(Vec2::new(-half.x, half.y));
+            builder.close();
+        }
+        // Triangle
+        _ => {
+            builder.move_to(Vec2::new(-half.x, -half.y));
+            builder.line_to(Vec2::new(half.x, -half.y));
+            builder.line_to(Vec2::new(0., half.y));
+            builder.close();
+        }
+    }
+
+    builder.build()
+}
+
+/// Index of the glyph drawn over a brick, a line for a normal brick, or following the remaining hits of a strong one.
+fn brick_glyph_index(kind: BrickKind, hit_points: u32) -> usize {
+    match kind {
+        BrickKind::Normal => 0,
+        BrickKind::Strong => (hit_points as usize).clamp(1, GLYPH_COUNT - 1),
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn brick_glyph_system(
+    mut commands: Commands,
+    brick_query: Query<(Entity, &BrickKind, &BrickHitPoints), With<Brick>>,
+    new_brick_query: Query<(Entity, &BrickKind, &BrickHitPoints), Added<Brick>>,
+    changed_brick_query: Query<Entity, (With<Brick>, Changed<BrickHitPoints>)>,
+    glyph_query: Query<(Entity, &Parent), With<BrickGlyph>>,
+    theme: Res<ActiveTheme>,
+    cfg: Res<GameConfig>,
+) {
+    let rebuild_all = cfg.is_changed() || theme.is_changed();
+
+    // Remove the outdated glyphs
+    for (glyph, parent) in glyph_query.iter() {
+        if rebuild_all || changed_brick_query.contains(parent.get()) {
+            commands.entity(glyph).despawn_recursive();
+        }
+    }
+
+    if !cfg.brick_glyphs {
+        return;
+    }
+
+    let spawn_glyph =
+        |commands: &mut Commands,
+         (brick, &kind, hit_points): (Entity, &BrickKind, &BrickHitPoints)| {
+            let glyph = commands
+                .spawn(GeometryBuilder::build_as(
+                    &glyph_path(brick_glyph_index(kind, hit_points.0), cfg.brick_size),
+                    DrawMode::Stroke(StrokeMode::new(theme.0.outline, 2.)),
+                    // Just above the brick
+                    Transform::from_xyz(0., 0., 0.1),
+                ))
+                .insert(BrickGlyph)
+                .id();
+            commands.entity(brick).add_child(glyph);
+        };
+
+    if rebuild_all {
+        for brick in brick_query.iter() {
+            spawn_glyph(&mut commands, brick);
+        }
+    } else {
+        for brick in new_brick_query.iter() {
+            spawn_glyph(&mut commands, brick);
+        }
+        for entity in changed_brick_query.iter() {
+            // Newly spawned bricks are also changed
+            if !new_brick_query.contains(entity) {
+                if let Ok(brick) = brick_query.get(entity) {
+                    spawn_glyph(&mut commands, brick);
+                }
+            }
+        }
+    }
+}
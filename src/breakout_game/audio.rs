@@ -0,0 +1,308 @@
+//! Audio related stuff.
+//!
+//! Sound effects are synthesized in code, no audio file is shipped with the game.
+//!
+//!  - Synthesize the sound effects and insert a [`SoundEffects`] resource at [`StartupStage::Startup`] stage.
+//!  - Play sound effects on gameplay events at [`GameStage::Events`] stage.
+
+use super::{
+    brick::area_y_offset,
+    events::{BallLost, BrickDestroyed, PaddleHit, WallHit},
+    resources::{GameConfig, SoundEffects},
+    GameStage,
+};
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::f32::consts::TAU;
+
+/// Sample rate of the synthesized sound effects in Hz.
+pub const SAMPLE_RATE: u32 = 22050;
+
+/// Frequency of the sound played when breaking a brick of the bottom row in Hz.
+const BRICK_BASE_FREQUENCY: f32 = 440.;
+
+/// Audio logic as a Bevy’s plugin.
+///
+/// Need [`bevy::audio::AudioPlugin`] to be added in Bevy’s app to works.
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(init_sound_effects_system)
+            .add_system_to_stage(GameStage::Events, play_sound_effects_system);
+    }
+}
+
+/// Volume of a sound effect over time, from zero to the peak then back to zero.
+#[derive(Clone, Copy, Debug)]
+pub struct Envelope {
+    /// Duration to reach the peak in seconds.
+    pub attack: f32,
+    /// Duration to go back from the peak to silence in seconds.
+    pub decay: f32,
+}
+
+impl Envelope {
+    /// Total duration of the envelope in seconds.
+    pub fn duration(&self) -> f32 {
+        self.attack + self.decay
+    }
+
+    /// Amplitude in range `0.0..=1.0` at time `t` in seconds.
+    pub fn amplitude(&self, t: f32) -> f32 {
+        if t < 0. {
+            0.
+        } else if t < self.attack {
+            t / self.attack
+        } else if t < self.duration() {
+            1. - (t - self.attack) / self.decay
+        } else {
+            0.
+        }
+    }
+}
+
+/// Synthesize a square wave whose frequency slides linearly from `start_frequency` to `end_frequency`.
+///
+/// Samples are in range `-1.0..=1.0`, the length is given by the envelope.
+pub fn square_sweep(start_frequency: f32, end_frequency: f32, envelope: Envelope) -> Vec<f32> {
+    let len = (envelope.duration() * SAMPLE_RATE as f32) as usize;
+    let mut phase = 0.;
+
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let frequency =
+                start_frequency + (end_frequency - start_frequency) * i as f32 / len as f32;
+            phase = (phase + TAU * frequency / SAMPLE_RATE as f32) % TAU;
+
+            let square = if phase < TAU / 2. { 1. } else { -1. };
+            square * envelope.amplitude(t)
+        })
+        .collect()
+}
+
+/// Synthesize a square wave at a constant `frequency`.
+pub fn square_blip(frequency: f32, envelope: Envelope) -> Vec<f32> {
+    square_sweep(frequency, frequency, envelope)
+}
+
+/// Synthesize white noise, the same `seed` always gives the same samples.
+pub fn noise_burst(seed: u64, envelope: Envelope) -> Vec<f32> {
+    let len = (envelope.duration() * SAMPLE_RATE as f32) as usize;
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..len)
+        .map(|i| rng.gen_range(-1.0..=1.0) * envelope.amplitude(i as f32 / SAMPLE_RATE as f32))
+        .collect()
+}
+
+/// Mix two sounds with the given gains, the result is as long as the longest one.
+pub fn mix(a: &[f32], a_gain: f32, b: &[f32], b_gain: f32) -> Vec<f32> {
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            let a = a.get(i).copied().unwrap_or_default() * a_gain;
+            let b = b.get(i).copied().unwrap_or_default() * b_gain;
+            (a + b).clamp(-1., 1.)
+        })
+        .collect()
+}
+
+/// Encode mono samples as a 16 bits PCM WAV file at [`SAMPLE_RATE`].
+pub fn encode_wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes()); // chunk size
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes()); // byte rate
+    wav.extend_from_slice(&2u16.to_le_bytes()); // block align
+    wav.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1., 1.) * i16::MAX as f32) as i16;
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    wav
+}
+
+/// Frequency of the sound played when breaking a brick of the given `row`, one semitone per row.
+pub fn brick_frequency(row: u32) -> f32 {
+    BRICK_BASE_FREQUENCY * 2f32.powf(row as f32 / 12.)
+}
+
+fn init_sound_effects_system(
+    mut commands: Commands,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+    cfg: Res<GameConfig>,
+) {
+    let mut add = |samples: Vec<f32>| {
+        audio_sources.add(AudioSource {
+            bytes: encode_wav(&samples).into(),
+        })
+    };
+
+    let wall_hit = add(square_blip(
+        220.,
+        Envelope {
+            attack: 0.002,
+            decay: 0.04,
+        },
+    ));
+    let paddle_hit = add(square_blip(
+        330.,
+        Envelope {
+            attack: 0.002,
+            decay: 0.06,
+        },
+    ));
+    let ball_lost = add(square_sweep(
+        400.,
+        80.,
+        Envelope {
+            attack: 0.01,
+            decay: 0.5,
+        },
+    ));
+
+    let brick_envelope = Envelope {
+        attack: 0.002,
+        decay: 0.1,
+    };
+    let brick_noise = noise_burst(0, brick_envelope);
+    let brick_destroyed = (0..cfg.brick_area_rows.max(1))
+        .map(|row| {
+            add(mix(
+                &square_blip(brick_frequency(row), brick_envelope),
+                0.7,
+                &brick_noise,
+                0.3,
+            ))
+        })
+        .collect();
+
+    commands.insert_resource(SoundEffects {
+        wall_hit,
+        paddle_hit,
+        brick_destroyed,
+        ball_lost,
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play_sound_effects_system(
+    audio: Res<Audio>,
+    sounds: Res<SoundEffects>,
+    mut wall_hit_events: EventReader<WallHit>,
+    mut paddle_hit_events: EventReader<PaddleHit>,
+    mut brick_destroyed_events: EventReader<BrickDestroyed>,
+    mut ball_lost_events: EventReader<BallLost>,
+    cfg: Res<GameConfig>,
+) {
+    let mut handles = Vec::new();
+
+    handles.extend(wall_hit_events.iter().map(|_| &sounds.wall_hit));
+    handles.extend(paddle_hit_events.iter().map(|_| &sounds.paddle_hit));
+    handles.extend(brick_destroyed_events.iter().map(|event| {
+        // Row of the brick in the brick’s spawning area
+        let bottom_row_y = area_y_offset(&cfg) - cfg.brick_area_rows as f32 * cfg.brick_size.y / 2.
+            + cfg.brick_size.y / 2.;
+        let row = ((event.position.y - bottom_row_y) / cfg.brick_size.y)
+            .round()
+            .clamp(0., (sounds.brick_destroyed.len() - 1) as f32) as usize;
+
+        &sounds.brick_destroyed[row]
+    }));
+    handles.extend(ball_lost_events.iter().map(|_| &sounds.ball_lost));
+
+    if cfg.audio_muted {
+        return;
+    }
+
+    for handle in handles {
+        audio.play_with_settings(
+            handle.clone(),
+            PlaybackSettings::ONCE.with_volume(cfg.audio_volume),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENVELOPE: Envelope = Envelope {
+        attack: 0.01,
+        decay: 0.1,
+    };
+
+    /// Highest absolute sample.
+    fn peak(samples: &[f32]) -> f32 {
+        samples
+            .iter()
+            .fold(0., |peak, sample| sample.abs().max(peak))
+    }
+
+    #[test]
+    fn length_follows_the_envelope() {
+        let len = (ENVELOPE.duration() * SAMPLE_RATE as f32) as usize;
+        assert_eq!(square_sweep(880., 220., ENVELOPE).len(), len);
+        assert_eq!(square_blip(440., ENVELOPE).len(), len);
+        assert_eq!(noise_burst(7, ENVELOPE).len(), len);
+    }
+
+    #[test]
+    fn peaks_are_audible_without_clipping() {
+        let short = square_blip(440., ENVELOPE);
+        let long = noise_burst(
+            7,
+            Envelope {
+                attack: 0.05,
+                decay: 0.3,
+            },
+        );
+        for samples in [
+            square_sweep(880., 220., ENVELOPE),
+            square_blip(440., ENVELOPE),
+            noise_burst(7, ENVELOPE),
+            mix(&short, 0.8, &long, 0.8),
+        ] {
+            let peak = peak(&samples);
+            assert!(peak > 0. && peak <= 1., "peak {}", peak);
+        }
+        assert_eq!(mix(&short, 0.8, &long, 0.8).len(), long.len());
+    }
+
+    #[test]
+    fn noise_is_deterministic() {
+        assert_eq!(noise_burst(42, ENVELOPE), noise_burst(42, ENVELOPE));
+        assert_ne!(noise_burst(42, ENVELOPE), noise_burst(43, ENVELOPE));
+    }
+
+    #[test]
+    fn wav_header_is_valid() {
+        let samples = square_blip(440., ENVELOPE);
+        let wav = encode_wav(&samples);
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(wav[offset..offset + 4].try_into().unwrap());
+
+        assert_eq!(wav.len(), 44 + samples.len() * 2);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32_at(4) as usize, wav.len() - 8);
+        assert_eq!(&wav[8..12], b"WAVE");
+        assert_eq!(&wav[12..16], b"fmt ");
+        assert_eq!(u32_at(24), SAMPLE_RATE);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(u32_at(40) as usize, samples.len() * 2);
+    }
+}
@@ -1,24 +1,51 @@
 //! Ball related stuff.
 //!
-//!  - Spawn the ball at [`StartupStage::PostStartup`] stage.
-//!  - Update the ball’s position in [`BallMoveState::FollowPaddle`] state at [`GameStage::Ball`].
-//!  - Process input to throw the ball at [`GameStage::Input`] stage.
+//!  - Spawn the ball of the first [`Player`] at [`StartupStage::PostStartup`] stage.
+//!  - Update the position of a [`StationaryBall`] on the paddle of its player,
+//!    at the contact point if it is a [`CaughtBall`], at [`GameStage::Ball`].
+//!  - Aim and process the input of each player to throw their ball, or throw it after a delay at [`GameStage::Input`] stage.
+//!  - Show the aiming arrow of the stationary ball at [`GameStage::Ui`] stage.
 //!  - Handle collision of the ball with entities marked with [`BallCollider`] at [`GameStage::Ball`] stage.
-//!  - Reset the ball when going out of window at [`GameStage::Init`] stage.
+//!    Only the colliders found near the ball by the [`super::broadphase`] are checked.
+//!    The ball’s speed depends on the current [`Level`] and its [`BallSpeed`].
+//!    The paddle catches the ball if [`GameConfig::paddle_catch`] is set or [`CATCH_EFFECT`] is active.
+//!    The ball belongs to the player of the last paddle it hit.
+//!  - Reset the ball and its speed when going out of window at [`GameStage::Init`] stage,
+//!    the player who lost it throws it again: the player guarding the side where it went out,
+//!    or in [`GameMode::Coop`] the player whose paddle was the nearest.
+//!  - Speed up the ball on paddle hits, back rows and strong bricks at [`GameStage::Events`] stage.
+//!  - Grant the [`CATCH_EFFECT`] when a strong brick is destroyed at [`GameStage::Events`] stage.
+//!  - Send [`BrickDestroyed`], [`PaddleHit`], [`WallHit`] and [`BallLost`] events.
 
 use super::{
+    brick::area_y_offset,
+    broadphase::update_broadphase_system,
     components::{
-        BallCollider, BoundingBox, Brick, FlyingBall, Paddle, Score, StationaryBall, Velocity,
+        AimArrow, Ball, BallCollider, BallLaunch, BallSpeed, BoundingBox, BrickColor,
+        BrickHitPoints, BrickKind, CaughtBall, FlyingBall, Paddle, Player, StationaryBall, Trail,
+        Velocity, Wall,
     },
-    resources::GameConfig,
+    events::{BallContact, BallLost, BrickDestroyed, PaddleHit, WallHit},
+    level::level_params,
+    paddle::paddle_side,
+    resources::{
+        ActiveEffect, ActiveEffects, ActiveTheme, Broadphase, GameConfig, GameMode, GameRng,
+        LaunchMode, Level, PlayerInputs,
+    },
+    theme::Theme,
     GameStage,
 };
 use bevy::{
     prelude::*,
     sprite::collide_aabb::{collide, Collision},
-    utils::HashSet,
+    utils::{HashMap, HashSet},
 };
 use bevy_prototype_lyon::prelude::*;
+use rand::Rng;
+use std::{f32::consts::PI, time::Duration};
+
+/// Name of the [`super::resources::ActiveEffect`] making the paddle catch the ball.
+pub const CATCH_EFFECT: &str = "Catch";
 
 /// Ball logic as a Bevy’s plugin. (see the game rules)
 pub struct BallPlugin;
@@ -27,13 +54,66 @@ impl Plugin for BallPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system_to_stage(StartupStage::PostStartup, spawn_ball_system)
             .add_system_to_stage(GameStage::Ball, follow_paddle_system)
-            .add_system_to_stage(GameStage::Input, throw_ball_system)
-            .add_system_to_stage(GameStage::Ball, ball_collision_system)
-            .add_system_to_stage(GameStage::Init, reset_ball_system);
+            .add_system_to_stage(GameStage::Input, aim_ball_system)
+            .add_system_to_stage(GameStage::Input, throw_ball_system.after(aim_ball_system))
+            .add_system_to_stage(
+                GameStage::Ball,
+                ball_collision_system.after(update_broadphase_system),
+            )
+            .add_system_to_stage(GameStage::Init, reset_ball_system)
+            .add_system_to_stage(GameStage::Events, ball_speed_up_system)
+            .add_system_to_stage(GameStage::Events, catch_effect_system)
+            .add_system_to_stage(GameStage::Ui, aim_arrow_system);
+    }
+}
+
+/// Launch state of a ball which has just been reset.
+fn ball_launch(cfg: &GameConfig) -> BallLaunch {
+    BallLaunch {
+        angle: 0.,
+        timer: cfg
+            .ball_auto_launch_delay
+            .map(|delay| Timer::from_seconds(delay, TimerMode::Once)),
+    }
+}
+
+/// Launch state of a ball caught at `offset` from the center of a paddle of the given `width`,
+/// thrown to the side of the contact point, the farther the steeper.
+fn caught_ball_launch(offset: f32, width: f32, cfg: &GameConfig) -> BallLaunch {
+    let max_angle = cfg.ball_launch_max_angle.to_radians();
+    BallLaunch {
+        angle: (offset / (width / 2.)).clamp(-1., 1.) * max_angle,
+        ..ball_launch(cfg)
     }
 }
 
-fn spawn_ball_system(mut commands: Commands, cfg: Res<GameConfig>) {
+/// Build an arrow pointing up, starting just above a ball of the given `radius`.
+fn aim_arrow_path(radius: f32) -> Path {
+    let start = radius + 4.;
+    let end = radius * 6.;
+    let head = radius;
+
+    let mut builder = PathBuilder::new();
+    builder.move_to(Vec2::new(0., start));
+    builder.line_to(Vec2::new(0., end));
+    builder.move_to(Vec2::new(-head, end - head));
+    builder.line_to(Vec2::new(0., end));
+    builder.line_to(Vec2::new(head, end - head));
+    builder.build()
+}
+
+fn spawn_ball_system(mut commands: Commands, theme: Res<ActiveTheme>, cfg: Res<GameConfig>) {
+    spawn_ball(&mut commands, &theme.0, &cfg, Player(0));
+}
+
+/// Spawn a stationary ball of `player` drawn with `theme`, with its aiming arrow.
+pub fn spawn_ball(
+    commands: &mut Commands,
+    theme: &Theme,
+    cfg: &GameConfig,
+    player: Player,
+) -> Entity {
+    let side = paddle_side(player, cfg);
     let shape = shapes::Circle {
         radius: cfg.ball_radius,
         ..Default::default()
@@ -42,94 +122,283 @@ fn spawn_ball_system(mut commands: Commands, cfg: Res<GameConfig>) {
     commands
         .spawn(GeometryBuilder::build_as(
             &shape,
-            DrawMode::Outlined {
-                fill_mode: FillMode::color(Color::BISQUE),
-                outline_mode: StrokeMode::color(Color::BLACK),
-            },
+            theme.outlined(theme.ball),
             Transform {
-                translation: Vec3::new(0., -cfg.window_height / 2. + cfg.ball_y_offset, cfg.ball_z),
+                translation: Vec3::new(
+                    0.,
+                    side * (-cfg.window_height / 2. + cfg.ball_y_offset),
+                    cfg.ball_z,
+                ),
                 ..Default::default()
             },
         ))
+        .insert(Ball)
         .insert(StationaryBall)
+        .insert(player)
+        .insert(Trail::default())
         .insert(BoundingBox(Vec2::new(
             cfg.ball_radius * 2.,
             cfg.ball_radius * 2.,
         )))
-        .insert(Velocity(Vec2::ZERO));
+        .insert(Velocity(Vec2::ZERO))
+        .insert(BallSpeed::default())
+        .insert(ball_launch(cfg))
+        .with_children(|parent| {
+            parent
+                .spawn(GeometryBuilder::build_as(
+                    &aim_arrow_path(cfg.ball_radius),
+                    DrawMode::Stroke(StrokeMode::new(theme.ball, 2.)),
+                    Transform::default(),
+                ))
+                .insert(AimArrow);
+        })
+        .id()
 }
 
+#[allow(clippy::type_complexity)]
 fn follow_paddle_system(
-    paddle_query: Query<&Transform, With<Paddle>>,
-    mut ball_query: Query<&mut Transform, (With<StationaryBall>, Without<Paddle>)>,
+    paddle_query: Query<(&Transform, &Player), With<Paddle>>,
+    mut ball_query: Query<
+        (&mut Transform, &Player, Option<&CaughtBall>),
+        (With<StationaryBall>, Without<Paddle>),
+    >,
+    cfg: Res<GameConfig>,
+) {
+    for (mut ball_tf, &player, caught) in ball_query.iter_mut() {
+        let paddle_tf = match paddle_query.iter().find(|(_, &paddle)| paddle == player) {
+            Some((paddle_tf, _)) => paddle_tf,
+            None => continue,
+        };
+
+        ball_tf.translation.x = paddle_tf.translation.x + caught.map_or(0., |caught| caught.offset);
+        ball_tf.translation.y = paddle_tf.translation.y
+            + paddle_side(player, &cfg)
+                * (cfg.paddle_size.y / 2. + cfg.ball_y_offset + cfg.ball_radius);
+    }
+}
+
+fn aim_ball_system(
+    inputs: Res<PlayerInputs>,
+    mut ball_query: Query<(&mut BallLaunch, &Player, Option<&CaughtBall>), With<StationaryBall>>,
+    paddle_query: Query<(&Velocity, &Player), With<Paddle>>,
     cfg: Res<GameConfig>,
 ) {
-    if let Ok(paddle_tf) = paddle_query.get_single() {
-        for mut ball_tf in ball_query.iter_mut() {
-            ball_tf.translation.x = paddle_tf.translation.x;
-            ball_tf.translation.y = paddle_tf.translation.y
-                + cfg.paddle_size.y / 2.
-                + cfg.ball_y_offset
-                + cfg.ball_radius;
+    let max_angle = cfg.ball_launch_max_angle.to_radians();
+    let dt = cfg.time_step();
+
+    for (mut launch, &player, caught) in ball_query.iter_mut() {
+        if let Some(timer) = &mut launch.timer {
+            timer.tick(Duration::from_secs_f32(dt));
+        }
+
+        // The angle of a caught ball is given by its contact point
+        if caught.is_some() {
+            continue;
+        }
+
+        match cfg.ball_launch_mode {
+            LaunchMode::Random => (),
+            LaunchMode::Aimed => {
+                let mut direction = 0.;
+                if inputs.get(player).aim_right {
+                    direction += 1.;
+                }
+                if inputs.get(player).aim_left {
+                    direction -= 1.;
+                }
+                launch.angle += direction * cfg.ball_aim_speed.to_radians() * dt;
+            }
+            LaunchMode::PaddleMotion => {
+                if let Some((paddle_v, _)) =
+                    paddle_query.iter().find(|(_, &paddle)| paddle == player)
+                {
+                    let paddle_speed = cfg.paddle_base_speed * cfg.paddle_sensitivity;
+                    launch.angle = paddle_v.0.x / paddle_speed * max_angle;
+                }
+            }
         }
+
+        launch.angle = launch.angle.clamp(-max_angle, max_angle);
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn throw_ball_system(
     mut commands: Commands,
-    keys: Res<Input<KeyCode>>,
-    mut ball_query: Query<(Entity, &mut Velocity), With<StationaryBall>>,
+    inputs: Res<PlayerInputs>,
+    mut ball_query: Query<
+        (
+            Entity,
+            &mut Velocity,
+            &mut BallSpeed,
+            &BallLaunch,
+            &Player,
+            Option<&CaughtBall>,
+        ),
+        (With<StationaryBall>, Without<Paddle>),
+    >,
+    level: Res<Level>,
+    mut rng: ResMut<GameRng>,
     cfg: Res<GameConfig>,
 ) {
-    if keys.just_pressed(KeyCode::Space) || keys.just_pressed(KeyCode::Up) {
-        if let Some((ball_entity, mut ball_v)) = ball_query.iter_mut().next() {
-            commands
-                .entity(ball_entity)
-                .remove::<StationaryBall>()
-                .insert(FlyingBall);
-            ball_v.0 = Vec2::new(0., -cfg.ball_base_speed);
+    let max_angle = cfg.ball_launch_max_angle.to_radians();
+    // Only one ball is thrown by key press
+    let mut key_pressed: HashSet<Player> = (0..cfg.game_mode.players())
+        .map(Player)
+        .filter(|&player| inputs.get(player).launch)
+        .collect();
+
+    for (ball_entity, mut ball_v, mut speed, launch, &player, caught) in ball_query.iter_mut() {
+        let timed_out = launch.timer.as_ref().map_or(false, Timer::finished);
+        if !key_pressed.remove(&player) && !timed_out {
+            continue;
         }
+
+        let angle = match cfg.ball_launch_mode {
+            _ if caught.is_some() => launch.angle,
+            LaunchMode::Random => rng.0.gen_range(-max_angle..=max_angle),
+            LaunchMode::Aimed | LaunchMode::PaddleMotion => launch.angle,
+        };
+
+        commands
+            .entity(ball_entity)
+            .remove::<StationaryBall>()
+            .remove::<CaughtBall>()
+            .insert(FlyingBall);
+        // A caught ball keeps its speed, a reset ball has no speed
+        speed.current = speed
+            .current
+            .max(level_params(level.number, &cfg).ball_speed);
+        ball_v.0 = Vec2::new(angle.sin(), angle.cos() * paddle_side(player, &cfg)) * speed.current;
     }
 }
 
-#[allow(clippy::type_complexity)]
+/// Bounce the ball off a collider on the side of the `collision`,
+/// the ball is moved against the collider and its velocity is reflected.
+pub fn bounce_ball(
+    collision: &Collision,
+    ball_translation: &mut Vec3,
+    ball_velocity: &mut Vec2,
+    ball_size: Vec2,
+    collider_translation: Vec3,
+    collider_size: Vec2,
+) {
+    match collision {
+        Collision::Right => {
+            ball_velocity.x = -ball_velocity.x;
+            ball_translation.x = collider_translation.x - collider_size.x / 2. - ball_size.x / 2.;
+        }
+        Collision::Left => {
+            ball_velocity.x = -ball_velocity.x;
+            ball_translation.x = collider_translation.x + collider_size.x / 2. + ball_size.x / 2.;
+        }
+        Collision::Top => {
+            ball_velocity.y = -ball_velocity.y;
+            ball_translation.y = collider_translation.y - collider_size.y / 2. - ball_size.y / 2.;
+        }
+        Collision::Bottom => {
+            ball_velocity.y = -ball_velocity.y;
+            ball_translation.y = collider_translation.y + collider_size.y / 2. + ball_size.y / 2.;
+        }
+        Collision::Inside => (),
+    }
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn ball_collision_system(
     mut commands: Commands,
     mut ball_query: Query<
-        (&mut Transform, &BoundingBox, &mut Velocity),
+        (
+            Entity,
+            &mut Transform,
+            &BoundingBox,
+            &mut Velocity,
+            &BallSpeed,
+            &mut BallLaunch,
+            &mut Player,
+        ),
         (With<FlyingBall>, Without<BallCollider>),
     >,
-    wall_query: Query<
+    mut wall_query: Query<
         (
             Entity,
             &Transform,
             &BoundingBox,
             Option<&Velocity>,
-            Option<&Brick>,
+            Option<&BrickKind>,
+            Option<&BrickColor>,
+            Option<&mut BrickHitPoints>,
+            Option<&Paddle>,
+            Option<&Player>,
+            Option<&Wall>,
         ),
         With<BallCollider>,
     >,
-    mut score_query: Query<&mut Score>,
+    mut brick_destroyed_events: EventWriter<BrickDestroyed>,
+    mut paddle_hit_events: EventWriter<PaddleHit>,
+    mut wall_hit_events: EventWriter<WallHit>,
+    mut ball_contact_events: EventWriter<BallContact>,
+    broadphase: Res<Broadphase>,
+    effects: Res<ActiveEffects>,
     cfg: Res<GameConfig>,
 ) {
+    let catch = cfg.paddle_catch || effects.0.iter().any(|effect| effect.name == CATCH_EFFECT);
     let mut despawned_entities = HashSet::new();
-    for (mut ball_tf, ball_bb, mut ball_v) in ball_query.iter_mut() {
-        // Clamp the ball in the window, except for the bottom
+    let mut candidates = Vec::new();
+    for (ball_entity, mut ball_tf, ball_bb, mut ball_v, speed, mut launch, mut ball_player) in
+        ball_query.iter_mut()
+    {
+        // Clamp the ball in the window, except for the bottom and the top goal of the versus mode
         ball_tf.translation.x = ball_tf.translation.x.clamp(
             -cfg.window_width / 2. + ball_bb.0.x / 2.,
             cfg.window_width / 2. - ball_bb.0.x / 2.,
         );
         let upper_limit = cfg.window_height / 2. - ball_bb.0.y / 2.;
-        if ball_tf.translation.y > upper_limit {
+        if cfg.game_mode != GameMode::Versus && ball_tf.translation.y > upper_limit {
             ball_tf.translation.y = upper_limit;
         }
 
-        // Check collision with "ball collider"
-        for (wall_entity, wall_tf, wall_bb, wall_v, brick) in wall_query.iter() {
-            if despawned_entities.contains(&wall_entity) {
+        // Check collision with "ball collider" near the ball
+        broadphase.query(ball_tf.translation.truncate(), ball_bb.0, &mut candidates);
+        // Nearest first then by position, the order doesn’t depend on entities to resume a saved game identically
+        let ball_translation = ball_tf.translation;
+        candidates.sort_by(|&a, &b| {
+            let key = |entity| {
+                wall_query.get(entity).map_or([f32::MAX; 3], |collider| {
+                    let translation = collider.1.translation;
+                    [
+                        translation.distance_squared(ball_translation),
+                        translation.x,
+                        translation.y,
+                    ]
+                })
+            };
+            let (a, b) = (key(a), key(b));
+            a[0].total_cmp(&b[0])
+                .then(a[1].total_cmp(&b[1]))
+                .then(a[2].total_cmp(&b[2]))
+        });
+        for &candidate in candidates.iter() {
+            if despawned_entities.contains(&candidate) {
                 continue;
             }
 
+            let (
+                wall_entity,
+                wall_tf,
+                wall_bb,
+                wall_v,
+                brick_kind,
+                brick_color,
+                hit_points,
+                paddle,
+                paddle_player,
+                wall,
+            ) = match wall_query.get_mut(candidate) {
+                Ok(collider) => collider,
+                Err(_) => continue,
+            };
+
             let wall_v = wall_v.copied().unwrap_or_default();
 
             let collision = collide(
@@ -140,42 +409,87 @@ fn ball_collision_system(
             );
 
             if let Some(collision) = collision {
-                match collision {
-                    Collision::Right => {
-                        ball_v.0.x = -ball_v.0.x;
-                        ball_tf.translation.x =
-                            wall_tf.translation.x - wall_bb.0.x / 2. - ball_bb.0.x / 2.;
-                    }
-                    Collision::Left => {
-                        ball_v.0.x = -ball_v.0.x;
-                        ball_tf.translation.x =
-                            wall_tf.translation.x + wall_bb.0.x / 2. + ball_bb.0.x / 2.;
-                    }
-                    Collision::Top => {
-                        ball_v.0.y = -ball_v.0.y;
-                        ball_tf.translation.y =
-                            wall_tf.translation.y - wall_bb.0.y / 2. - ball_bb.0.y / 2.;
-                    }
-                    Collision::Bottom => {
-                        ball_v.0.y = -ball_v.0.y;
-                        ball_tf.translation.y =
-                            wall_tf.translation.y + wall_bb.0.y / 2. + ball_bb.0.y / 2.;
-                    }
-                    _ => (),
-                }
+                // Only from the bricks’ side, the paddle doesn’t catch the ball by its sides
+                let facing = match paddle_player {
+                    Some(&player) if paddle_side(player, &cfg) < 0. => Collision::Top,
+                    _ => Collision::Bottom,
+                };
+                let caught = catch && paddle.is_some() && collision == facing;
+
+                bounce_ball(
+                    &collision,
+                    &mut ball_tf.translation,
+                    &mut ball_v.0,
+                    ball_bb.0,
+                    wall_tf.translation,
+                    wall_bb.0,
+                );
+                ball_contact_events.send(BallContact {
+                    ball: ball_entity,
+                    collider: wall_entity,
+                    position: ball_tf.translation.truncate(),
+                    side: collision,
+                });
 
                 ball_v.0 = (ball_v.0.normalize_or_zero() + wall_v.0.normalize_or_zero())
                     .normalize_or_zero()
-                    * cfg.ball_base_speed;
+                    * speed.current;
 
-                if brick.is_some() {
+                if let Some(&player) = paddle_player {
+                    *ball_player = player;
+                }
+
+                if paddle.is_some() {
+                    paddle_hit_events.send(PaddleHit {
+                        ball: ball_entity,
+                        paddle: wall_entity,
+                        position: ball_tf.translation.truncate(),
+                        velocity: ball_v.0,
+                    });
+                }
+
+                if caught {
+                    let offset = ball_tf.translation.x - wall_tf.translation.x;
+                    *launch = caught_ball_launch(offset, wall_bb.0.x, &cfg);
+                    ball_v.0 = Vec2::ZERO;
+                    commands
+                        .entity(ball_entity)
+                        .remove::<FlyingBall>()
+                        .insert(StationaryBall)
+                        .insert(CaughtBall { offset });
+                    break;
+                }
+
+                if wall.is_some() {
+                    wall_hit_events.send(WallHit {
+                        ball: ball_entity,
+                        wall: wall_entity,
+                        position: ball_tf.translation.truncate(),
+                        velocity: ball_v.0,
+                    });
+                }
+
+                if let Some(mut hit_points) = hit_points {
+                    if hit_points.0 > 1 {
+                        hit_points.0 -= 1;
+                        continue;
+                    }
+                }
+
+                if let Some(&kind) = brick_kind {
                     if let Some(mut entity_commands) = commands.get_entity(wall_entity) {
-                        entity_commands.despawn();
+                        // Recursive to despawn the glyph too
+                        entity_commands.despawn_recursive();
                         despawned_entities.insert(wall_entity);
 
-                        if let Ok(mut score) = score_query.get_single_mut() {
-                            score.0 += cfg.score_brick;
-                        }
+                        brick_destroyed_events.send(BrickDestroyed {
+                            entity: wall_entity,
+                            ball: ball_entity,
+                            position: wall_tf.translation.truncate(),
+                            kind,
+                            color: brick_color.map_or(Color::WHITE, |color| color.0),
+                            velocity: ball_v.0,
+                        });
                     }
                 }
             }
@@ -183,18 +497,55 @@ fn ball_collision_system(
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn reset_ball_system(
     mut commands: Commands,
-    mut query: Query<(Entity, &Transform, &mut Velocity), With<FlyingBall>>,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Velocity,
+            &mut BallSpeed,
+            &mut BallLaunch,
+            &mut Player,
+        ),
+        With<FlyingBall>,
+    >,
+    paddle_query: Query<(&Transform, &Player), With<Paddle>>,
+    mut ball_lost_events: EventWriter<BallLost>,
     cfg: Res<GameConfig>,
 ) {
-    for (entity, &tf, mut velocity) in query.iter_mut() {
+    for (entity, &tf, mut velocity, mut speed, mut launch, mut player) in query.iter_mut() {
         if tf.translation.x < -cfg.window_width / 2. - 200.
             || tf.translation.x > cfg.window_width / 2. + 200.
             || tf.translation.y < -cfg.window_height / 2. - 200.
             || tf.translation.y > cfg.window_height / 2. + 200.
         {
+            // The player who missed the ball throws it again
+            *player = match cfg.game_mode {
+                GameMode::Versus if tf.translation.y > 0. => Player(1),
+                GameMode::Coop => paddle_query
+                    .iter()
+                    .min_by(|(a, _), (b, _)| {
+                        let distance = |paddle_tf: &Transform| {
+                            (paddle_tf.translation.x - tf.translation.x).abs()
+                        };
+                        distance(a).total_cmp(&distance(b))
+                    })
+                    .map_or(Player(0), |(_, &player)| player),
+                _ => Player(0),
+            };
+            ball_lost_events.send(BallLost {
+                entity,
+                player: *player,
+                position: tf.translation.truncate(),
+                velocity: velocity.0,
+            });
+
             velocity.0 = Vec2::ZERO;
+            // The speed of the level is set again when the ball is thrown
+            *speed = BallSpeed::default();
+            *launch = ball_launch(&cfg);
             commands
                 .entity(entity)
                 .remove::<FlyingBall>()
@@ -202,3 +553,185 @@ fn reset_ball_system(
         }
     }
 }
+
+fn catch_effect_system(
+    mut brick_destroyed_events: EventReader<BrickDestroyed>,
+    mut effects: ResMut<ActiveEffects>,
+    cfg: Res<GameConfig>,
+) {
+    let strong_brick_destroyed = brick_destroyed_events
+        .iter()
+        .any(|event| event.kind == BrickKind::Strong);
+
+    if let (true, Some(duration)) = (strong_brick_destroyed, cfg.catch_effect_duration) {
+        let timer = Timer::from_seconds(duration, TimerMode::Once);
+        // Restarted if already active
+        match effects
+            .0
+            .iter_mut()
+            .find(|effect| effect.name == CATCH_EFFECT)
+        {
+            Some(effect) => effect.timer = timer,
+            None => effects.0.push(ActiveEffect {
+                name: CATCH_EFFECT.to_string(),
+                timer,
+            }),
+        }
+    }
+}
+
+fn ball_speed_up_system(
+    mut paddle_hit_events: EventReader<PaddleHit>,
+    mut brick_destroyed_events: EventReader<BrickDestroyed>,
+    mut ball_query: Query<(Entity, &Transform, &mut Velocity, &mut BallSpeed), With<FlyingBall>>,
+    level: Res<Level>,
+    cfg: Res<GameConfig>,
+) {
+    let mut speed_ups: HashMap<Entity, u32> = HashMap::new();
+
+    for event in paddle_hit_events.iter() {
+        if let Ok((_, _, _, mut speed)) = ball_query.get_mut(event.ball) {
+            speed.paddle_hits += 1;
+            if cfg.ball_speed_up_paddle_hits > 0
+                && speed.paddle_hits >= cfg.ball_speed_up_paddle_hits
+            {
+                speed.paddle_hits = 0;
+                *speed_ups.entry(event.ball).or_default() += 1;
+            }
+        }
+    }
+
+    for event in brick_destroyed_events.iter() {
+        if cfg.ball_speed_up_strong_bricks && event.kind == BrickKind::Strong {
+            *speed_ups.entry(event.ball).or_default() += 1;
+        }
+    }
+
+    // Bottom of the back rows, the top rows of the brick’s spawning area
+    let back_rows_y = area_y_offset(&cfg)
+        + (cfg.brick_area_rows as f32 / 2. - cfg.ball_speed_up_back_rows as f32) * cfg.brick_size.y;
+    let level_speed = level_params(level.number, &cfg).ball_speed;
+
+    for (entity, tf, mut velocity, mut speed) in ball_query.iter_mut() {
+        let mut count = speed_ups.get(&entity).copied().unwrap_or(0);
+        if cfg.ball_speed_up_back_rows > 0
+            && !speed.reached_back_rows
+            && tf.translation.y >= back_rows_y
+        {
+            speed.reached_back_rows = true;
+            count += 1;
+        }
+
+        if count > 0 {
+            speed.current = (speed.current + level_speed * cfg.ball_speed_up_step * count as f32)
+                .min(level_speed * cfg.ball_speed_max);
+            velocity.0 = velocity.0.normalize_or_zero() * speed.current;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn aim_arrow_system(
+    ball_query: Query<(
+        &BallLaunch,
+        &Player,
+        Option<&StationaryBall>,
+        Option<&CaughtBall>,
+    )>,
+    mut arrow_query: Query<
+        (&Parent, &mut Transform, &mut Visibility, &mut DrawMode),
+        With<AimArrow>,
+    >,
+    theme: Res<ActiveTheme>,
+    cfg: Res<GameConfig>,
+) {
+    for (parent, mut tf, mut visibility, mut draw_mode) in arrow_query.iter_mut() {
+        if let Ok((launch, &player, stationary, caught)) = ball_query.get(parent.get()) {
+            // The direction of a random launch is unknown until the ball is thrown
+            let visible = stationary.is_some()
+                && (caught.is_some() || cfg.ball_launch_mode != LaunchMode::Random);
+            if visibility.is_visible != visible {
+                visibility.is_visible = visible;
+            }
+            if visible {
+                // The arrow points down from the top paddle
+                tf.rotation = if paddle_side(player, &cfg) < 0. {
+                    Quat::from_rotation_z(PI + launch.angle)
+                } else {
+                    Quat::from_rotation_z(-launch.angle)
+                };
+            }
+        }
+
+        if theme.is_changed() {
+            *draw_mode = DrawMode::Stroke(StrokeMode::new(theme.0.ball, 2.));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::breakout_game::resources::PlayerInput;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    /// App following and throwing the stationary balls, with a paddle at the center of the window.
+    fn app(cfg: &GameConfig) -> App {
+        let mut app = App::new();
+        app.insert_resource(cfg.clone())
+            .insert_resource(Level::default())
+            .insert_resource(GameRng(ChaCha8Rng::seed_from_u64(0)))
+            .insert_resource(PlayerInputs::default())
+            .add_system(follow_paddle_system)
+            .add_system(throw_ball_system);
+        app.world.spawn((
+            Paddle,
+            Player(0),
+            Transform::default(),
+            Velocity(Vec2::ZERO),
+        ));
+        app
+    }
+
+    #[test]
+    fn caught_ball_keeps_its_offset_and_is_thrown_at_its_angle() {
+        let cfg = GameConfig::default();
+        let offset = cfg.paddle_size.x / 4.;
+        let mut app = app(&cfg);
+        let ball = app
+            .world
+            .spawn((
+                Ball,
+                Player(0),
+                StationaryBall,
+                CaughtBall { offset },
+                caught_ball_launch(offset, cfg.paddle_size.x, &cfg),
+                BallSpeed::default(),
+                Velocity(Vec2::ZERO),
+                Transform::default(),
+            ))
+            .id();
+
+        // Rides along with the paddle
+        let mut paddle_query = app.world.query_filtered::<&mut Transform, With<Paddle>>();
+        paddle_query.single_mut(&mut app.world).translation.x = 100.;
+        app.update();
+        let ball_x = app.world.get::<Transform>(ball).unwrap().translation.x;
+        assert_eq!(ball_x, 100. + offset);
+        assert!(app.world.get::<StationaryBall>(ball).is_some());
+
+        // Halfway to the paddle’s edge, thrown at half the maximum angle toward its side
+        app.insert_resource(PlayerInputs(vec![PlayerInput {
+            launch: true,
+            ..Default::default()
+        }]));
+        app.update();
+        assert!(app.world.get::<FlyingBall>(ball).is_some());
+        assert!(app.world.get::<CaughtBall>(ball).is_none());
+        let velocity = app.world.get::<Velocity>(ball).unwrap().0;
+        let angle = velocity.x.atan2(velocity.y);
+        let expected = cfg.ball_launch_max_angle.to_radians() / 2.;
+        assert!((angle - expected).abs() < 1e-4, "angle {}", angle);
+    }
+}
@@ -1,16 +1,30 @@
 //! Brick related stuff.
 //!
-//!  - Spawn or respawn bricks at [`GameStage::Init`].
+//!  - Spawn or respawn bricks following the difficulty of the current [`Level`] at [`GameStage::Init`].
+//!    Bricks are placed by the level’s [`super::generator`], or following the [`CustomLevel`] if there is one.
 //!  - Insert a [`BrickRespawn`] resource.
+//!  - Send [`LevelCleared`] when all bricks have been destroyed.
+//!  - Replace all bricks on [`GameOver`] at [`GameStage::Events`] stage.
+//!    Moving bricks are handled by [`super::motion`].
+//!  - Redraw bricks losing hit points at [`GameStage::Ui`] stage.
 
 use super::{
-    components::{BallCollider, BoundingBox, Brick},
-    resources::{BrickRespawn, GameConfig},
+    components::{
+        BallCollider, BoundingBox, Brick, BrickCell, BrickColor, BrickHitPoints, BrickKind,
+        BrickMotion, Velocity,
+    },
+    events::{GameOver, LevelCleared},
+    generator::generate_level,
+    level::{level_params, BrickMovement},
+    resources::{ActiveTheme, BrickRespawn, CustomLevel, GameConfig, GameMode, GameRng, Level},
+    theme::Theme,
     GameStage,
 };
 use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::*;
-use rand::{seq::SliceRandom, thread_rng, Rng};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{f32::consts::PI, time::Duration};
 
 /// Brick logic as a Bevy’s plugin. (see the game rules)
 pub struct BrickPlugin;
@@ -18,6 +32,9 @@ pub struct BrickPlugin;
 impl Plugin for BrickPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_to_stage(GameStage::Init, spawn_brick_system)
+            .add_system_to_stage(GameStage::Events, game_over_brick_system)
+            .add_system_to_stage(GameStage::Ui, brick_hit_points_system)
+            .insert_resource(CustomLevel::default())
             .insert_resource(BrickRespawn {
                 immediate_spawn: true,
                 timer: Timer::from_seconds(1., TimerMode::Once),
@@ -25,70 +42,205 @@ impl Plugin for BrickPlugin {
     }
 }
 
+/// Everything needed to spawn a brick, also used to save it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BrickState {
+    /// Position of the brick’s center.
+    pub translation: Vec3,
+    /// Cell of the brick in the brick’s spawning area.
+    pub cell: BrickCell,
+    /// Kind of the brick.
+    pub kind: BrickKind,
+    /// Fill color of the brick.
+    pub color: Color,
+    /// Remaining hits to destroy the brick.
+    pub hit_points: u32,
+    /// Movement of the brick, if it moves.
+    pub motion: Option<BrickMotion>,
+}
+
+/// Spawn a brick drawn with `theme`.
+pub fn spawn_brick(
+    commands: &mut Commands,
+    state: &BrickState,
+    theme: &Theme,
+    cfg: &GameConfig,
+) -> Entity {
+    let shape = shapes::Rectangle {
+        extents: cfg.brick_size,
+        ..Default::default()
+    };
+
+    let mut brick = commands.spawn(GeometryBuilder::build_as(
+        &shape,
+        theme.outlined_brick(state.color, state.hit_points),
+        Transform {
+            translation: state.translation,
+            ..Default::default()
+        },
+    ));
+    brick
+        .insert(BallCollider)
+        .insert(Brick)
+        .insert(state.kind)
+        .insert(state.cell)
+        .insert(BrickColor(state.color))
+        .insert(BrickHitPoints(state.hit_points))
+        .insert(BoundingBox(cfg.brick_size));
+
+    if let Some(motion) = state.motion {
+        brick.insert(Velocity(Vec2::ZERO)).insert(motion);
+    }
+
+    brick.id()
+}
+
+#[allow(clippy::too_many_arguments)]
 fn spawn_brick_system(
     mut commands: Commands,
-    time: Res<Time>,
     mut brick_respawn: ResMut<BrickRespawn>,
     bricks_query: Query<&Brick>,
+    mut level_cleared_events: EventWriter<LevelCleared>,
+    level: Res<Level>,
+    custom_level: Res<CustomLevel>,
+    mut rng: ResMut<GameRng>,
+    theme: Res<ActiveTheme>,
     cfg: Res<GameConfig>,
 ) {
-    if bricks_query.is_empty() {
+    if bricks_query.is_empty() && !brick_respawn.immediate_spawn {
+        // The timer is paused as long as bricks remain
+        if brick_respawn.timer.paused() {
+            level_cleared_events.send(LevelCleared);
+        }
         brick_respawn.timer.unpause();
     }
 
-    brick_respawn.timer.tick(time.delta());
+    brick_respawn
+        .timer
+        .tick(Duration::from_secs_f32(cfg.time_step()));
 
     if brick_respawn.immediate_spawn || brick_respawn.timer.just_finished() {
         brick_respawn.timer.reset();
         brick_respawn.timer.pause();
         brick_respawn.immediate_spawn = false;
 
-        let mut rng = thread_rng();
-
-        let shape = shapes::Rectangle {
-            extents: cfg.brick_size,
-            ..Default::default()
+        let params = level_params(level.number, &cfg);
+        let design = match &custom_level.0 {
+            Some(design) => design.clone(),
+            None => generate_level(
+                &params,
+                cfg.brick_area_cols,
+                cfg.brick_area_rows,
+                rng.0.gen(),
+                &cfg,
+            ),
         };
 
-        let brick_area_size = Vec2::new(
-            cfg.brick_area_cols as f32 * cfg.brick_size.x,
-            cfg.brick_area_rows as f32 * cfg.brick_size.y,
-        );
-
-        // bottom left brick
-        let first_brick_translation = Vec2::new(
-            -brick_area_size.x / 2. + cfg.brick_size.x / 2.,
-            -brick_area_size.y / 2. + cfg.brick_size.y / 2. + cfg.brick_area_y_offset,
-        );
-
-        for col in 0..cfg.brick_area_cols {
-            for row in 0..cfg.brick_area_rows {
-                if rng.gen_bool(cfg.brick_spawn_probability) {
-                    let brick_offset =
-                        Vec2::new(col as f32 * cfg.brick_size.x, row as f32 * cfg.brick_size.y);
-                    let translation = (first_brick_translation + brick_offset).extend(cfg.brick_z);
-                    let color = cfg
-                        .brick_colors
-                        .choose(&mut rng)
-                        .copied()
-                        .unwrap_or(Color::WHITE);
-                    commands
-                        .spawn(GeometryBuilder::build_as(
-                            &shape,
-                            DrawMode::Outlined {
-                                fill_mode: FillMode::color(color),
-                                outline_mode: StrokeMode::color(Color::BLACK),
-                            },
-                            Transform {
-                                translation,
-                                ..Default::default()
-                            },
-                        ))
-                        .insert(BallCollider)
-                        .insert(Brick)
-                        .insert(BoundingBox(cfg.brick_size));
+        // Defined by the level, or following the difficulty curve
+        let mut movement = design.movement.unwrap_or(params.movement);
+        // Bricks placed by hand may be too close to move
+        if !design
+            .bricks
+            .iter()
+            .all(|brick| movement.allows(brick.col, brick.row))
+        {
+            movement = BrickMovement::Static;
+        }
+
+        // The gameplay’s RNG mustn’t depend on the theme, which may differ between peers
+        let mut cosmetic_rng = rand::thread_rng();
+        for brick in design.bricks.iter() {
+            let translation =
+                cell_translation(brick.col, brick.row, design.cols, design.rows, &cfg);
+            let kind = if brick.hit_points > 1 {
+                BrickKind::Strong
+            } else {
+                BrickKind::Normal
+            };
+            let color = brick.color.unwrap_or_else(|| {
+                theme
+                    .0
+                    .brick_color(brick.row, design.rows, kind, &mut cosmetic_rng)
+            });
+
+            let motion = (movement != BrickMovement::Static).then(|| {
+                // Neighbor groups of 2x2 bricks in opposite phase, one cell apart
+                let group = (brick.col / 3 + brick.row / 3) % 2;
+                BrickMotion {
+                    movement,
+                    origin: translation.truncate(),
+                    phase: group as f32 * PI,
+                    direction: 1.,
+                    elapsed: 0.,
                 }
-            }
+            });
+
+            let state = BrickState {
+                translation,
+                cell: BrickCell {
+                    col: brick.col,
+                    row: brick.row,
+                },
+                kind,
+                color,
+                hit_points: brick.hit_points.max(1),
+                motion,
+            };
+            spawn_brick(&mut commands, &state, &theme.0, &cfg);
+        }
+    }
+}
+
+/// Position of the center of the brick at `col` and `row` of a brick’s spawning area of `cols` by `rows`.
+pub fn cell_translation(col: u32, row: u32, cols: u32, rows: u32, cfg: &GameConfig) -> Vec3 {
+    let corner = area_corner(cols, rows, cfg);
+    let offset = Vec2::new(col as f32 + 0.5, row as f32 + 0.5) * cfg.brick_size;
+    (corner + offset).extend(cfg.brick_z)
+}
+
+/// Column and row of the cell at `position` in a brick’s spawning area of `cols` by `rows`, if it is in the area.
+pub fn position_cell(position: Vec2, cols: u32, rows: u32, cfg: &GameConfig) -> Option<(u32, u32)> {
+    let cell = ((position - area_corner(cols, rows, cfg)) / cfg.brick_size).floor();
+    let in_area = cell.x >= 0. && cell.y >= 0. && cell.x < cols as f32 && cell.y < rows as f32;
+    in_area.then_some((cell.x as u32, cell.y as u32))
+}
+
+/// Bottom left corner of a brick’s spawning area of `cols` by `rows`.
+fn area_corner(cols: u32, rows: u32, cfg: &GameConfig) -> Vec2 {
+    let brick_area_size = Vec2::new(cols as f32, rows as f32) * cfg.brick_size;
+    Vec2::new(
+        -brick_area_size.x / 2.,
+        -brick_area_size.y / 2. + area_y_offset(cfg),
+    )
+}
+
+/// Brick’s spawning area position along Y-axis, centered between the paddles in [`GameMode::Versus`].
+pub fn area_y_offset(cfg: &GameConfig) -> f32 {
+    match cfg.game_mode {
+        GameMode::Versus => 0.,
+        _ => cfg.brick_area_y_offset,
+    }
+}
+
+fn game_over_brick_system(
+    mut commands: Commands,
+    mut game_over_events: EventReader<GameOver>,
+    mut brick_respawn: ResMut<BrickRespawn>,
+    bricks_query: Query<Entity, With<Brick>>,
+) {
+    if game_over_events.iter().next().is_some() {
+        for entity in bricks_query.iter() {
+            commands.entity(entity).despawn_recursive();
         }
+        brick_respawn.immediate_spawn = true;
+    }
+}
+
+fn brick_hit_points_system(
+    mut brick_query: Query<(&mut DrawMode, &BrickColor, &BrickHitPoints), Changed<BrickHitPoints>>,
+    theme: Res<ActiveTheme>,
+) {
+    for (mut draw_mode, color, hit_points) in brick_query.iter_mut() {
+        *draw_mode = theme.0.outlined_brick(color.0, hit_points.0);
     }
 }
@@ -0,0 +1,152 @@
+//! Broadphase of the ball’s collisions.
+//!
+//! The [`Broadphase`] grid maps each cell to the [`BallCollider`] overlapping it,
+//! so the ball only checks the colliders of the cells it overlaps instead of all of them.
+//! Only the colliders which have moved, spawned or despawned since the previous frame are updated.
+//!
+//!  - Insert a [`Broadphase`] resource.
+//!  - Update the colliders which spawned or moved at [`GameStage::Ball`] stage, before the ball’s collisions.
+//!    The whole grid is rebuilt when [`GameConfig::broadphase_cell_size`] changes.
+//!  - Remove the despawned colliders at [`CoreStage::PostUpdate`] stage.
+
+use super::{
+    components::{BallCollider, BoundingBox},
+    resources::{Broadphase, GameConfig},
+    GameStage,
+};
+use bevy::prelude::*;
+
+/// Broadphase logic as a Bevy’s plugin.
+pub struct BroadphasePlugin;
+
+impl Plugin for BroadphasePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Broadphase::default())
+            .add_system_to_stage(GameStage::Ball, update_broadphase_system)
+            // Removed components are only tracked until the end of the frame
+            .add_system_to_stage(CoreStage::PostUpdate, remove_broadphase_system);
+    }
+}
+
+impl Broadphase {
+    /// Empty grid with cells of `cell_size` pixels.
+    pub fn new(cell_size: f32) -> Broadphase {
+        Broadphase {
+            cell_size,
+            ..Default::default()
+        }
+    }
+
+    /// Number of colliders in the grid.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if there is no collider in the grid.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// First and last cells overlapped by a box of `size` centered on `center`.
+    fn cell_range(&self, center: Vec2, size: Vec2) -> (IVec2, IVec2) {
+        let cell_size = self.cell_size.max(1.);
+        (
+            ((center - size / 2.) / cell_size).floor().as_ivec2(),
+            ((center + size / 2.) / cell_size).floor().as_ivec2(),
+        )
+    }
+
+    /// Insert or move `entity`, a box of `size` centered on `center`.
+    ///
+    /// Nothing changes as long as the entity stays in the same cells.
+    pub fn insert(&mut self, entity: Entity, center: Vec2, size: Vec2) {
+        let range = self.cell_range(center, size);
+        match self.entries.get(&entity) {
+            Some(&previous) if previous == range => return,
+            Some(_) => self.remove(entity),
+            None => (),
+        }
+
+        let (min, max) = range;
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(entity);
+            }
+        }
+        self.entries.insert(entity, range);
+    }
+
+    /// Remove `entity` from all its cells, if it is in the grid.
+    pub fn remove(&mut self, entity: Entity) {
+        if let Some((min, max)) = self.entries.remove(&entity) {
+            for x in min.x..=max.x {
+                for y in min.y..=max.y {
+                    let cell = IVec2::new(x, y);
+                    if let Some(entities) = self.cells.get_mut(&cell) {
+                        entities.retain(|&other| other != entity);
+                        if entities.is_empty() {
+                            self.cells.remove(&cell);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replace `candidates` by the colliders sharing a cell with a box of `size` centered on `center`.
+    ///
+    /// Candidates are sorted and unique, they still need a narrowphase check.
+    pub fn query(&self, center: Vec2, size: Vec2, candidates: &mut Vec<Entity>) {
+        candidates.clear();
+
+        let (min, max) = self.cell_range(center, size);
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                if let Some(entities) = self.cells.get(&IVec2::new(x, y)) {
+                    candidates.extend_from_slice(entities);
+                }
+            }
+        }
+
+        // Colliders overlapping several cells are found several times
+        candidates.sort_unstable();
+        candidates.dedup();
+    }
+}
+
+/// Also public to run the ball’s collisions after it.
+#[allow(clippy::type_complexity)]
+pub fn update_broadphase_system(
+    mut broadphase: ResMut<Broadphase>,
+    collider_query: Query<(Entity, &Transform, &BoundingBox), With<BallCollider>>,
+    changed_query: Query<
+        (Entity, &Transform, &BoundingBox),
+        (
+            With<BallCollider>,
+            Or<(Changed<Transform>, Changed<BoundingBox>)>,
+        ),
+    >,
+    cfg: Res<GameConfig>,
+) {
+    if broadphase.cell_size != cfg.broadphase_cell_size {
+        *broadphase = Broadphase::new(cfg.broadphase_cell_size);
+        for (entity, tf, bb) in collider_query.iter() {
+            broadphase.insert(entity, tf.translation.truncate(), bb.0);
+        }
+        return;
+    }
+
+    // Also true for the colliders which have just spawned
+    for (entity, tf, bb) in changed_query.iter() {
+        broadphase.insert(entity, tf.translation.truncate(), bb.0);
+    }
+}
+
+fn remove_broadphase_system(
+    mut broadphase: ResMut<Broadphase>,
+    removed: RemovedComponents<BallCollider>,
+) {
+    for entity in removed.iter() {
+        broadphase.remove(entity);
+    }
+}
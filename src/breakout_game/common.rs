@@ -16,6 +16,6 @@ impl Plugin for CommonPlugin {
 
 fn movement_system(mut query: Query<(&mut Transform, &Velocity)>, cfg: Res<GameConfig>) {
     for (mut tf, velocity) in query.iter_mut() {
-        tf.translation += velocity.0.extend(0.) * (1. / cfg.framerate);
+        tf.translation += velocity.0.extend(0.) * cfg.time_step();
     }
 }
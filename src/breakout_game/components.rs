@@ -1,6 +1,13 @@
 //! All the components used by the game.
 
-use bevy::prelude::{Component, Vec2};
+use super::level::BrickMovement;
+use bevy::prelude::{Color, Component, Entity, Timer, Vec2};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// A marker component to identify balls, stationary or flying.
+#[derive(Component)]
+pub struct Ball;
 
 /// A marker component to identify a stationary ball.
 #[derive(Component)]
@@ -18,10 +25,97 @@ pub struct BallCollider;
 #[derive(Component)]
 pub struct Brick;
 
+/// Kind of a brick.
+#[derive(Component, Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrickKind {
+    /// A regular brick, destroyed on the first hit.
+    #[default]
+    Normal,
+    /// A brick destroyed after several hits, see [`BrickHitPoints`].
+    Strong,
+}
+
+/// Movement of a brick, see [`super::motion`].
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct BrickMotion {
+    /// How the brick moves.
+    pub movement: BrickMovement,
+    /// Center of the movement, the cell of the brick.
+    pub origin: Vec2,
+    /// Starting angle of an orbit in radians.
+    pub phase: f32,
+    /// Direction of an oscillation, `-1` or `1`, reversed by walls.
+    pub direction: f32,
+    /// Time since the brick has been spawned in seconds.
+    pub elapsed: f32,
+}
+
+/// Remaining hits to destroy a [`BrickKind::Strong`] brick.
+#[derive(Component, Clone, Copy)]
+pub struct BrickHitPoints(pub u32);
+
+/// Current speed of a ball and the progress of its speed-up rules.
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Default, Debug)]
+pub struct BallSpeed {
+    /// Speed in pixels per second, set to the level’s ball speed when the ball is thrown.
+    pub current: f32,
+    /// Paddle hits since the last speed-up due to the paddle.
+    pub paddle_hits: u32,
+    /// If true, the ball has reached the back rows since it has been reset.
+    pub reached_back_rows: bool,
+}
+
+/// A stationary ball caught by the paddle, it follows the paddle at the contact point.
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct CaughtBall {
+    /// Offset from the paddle’s center along X-axis in pixels.
+    pub offset: f32,
+}
+
+/// Direction of a stationary ball when it is thrown.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct BallLaunch {
+    /// Angle from the vertical in radians, positive to the right.
+    pub angle: f32,
+    /// The ball is thrown automatically when it finishes, see [`super::resources::GameConfig`].
+    pub timer: Option<Timer>,
+}
+
+/// A marker component to identify the arrow showing the direction of a stationary ball.
+#[derive(Component)]
+pub struct AimArrow;
+
+/// Position of a brick in the brick’s spawning area, the bottom left cell is `(0, 0)`.
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct BrickCell {
+    /// Column, from left to right.
+    pub col: u32,
+    /// Row, from bottom to top.
+    pub row: u32,
+}
+
+/// Fill color of a brick, kept to color the debris when the brick is destroyed.
+#[derive(Component, Clone, Copy)]
+pub struct BrickColor(pub Color);
+
+/// A marker component to identify glyphs drawn over bricks, as children of the bricks.
+#[derive(Component)]
+pub struct BrickGlyph;
+
+/// A marker component to identify the entities of the level editor, the grid, the bricks and the texts.
+#[derive(Component)]
+pub struct EditorRoot;
+
 /// A marker component to identify paddles.
 #[derive(Component)]
 pub struct Paddle;
 
+/// A player, starting at 0, owning a paddle, a score and the balls they hit last.
+///
+/// In [`super::resources::GameMode::Versus`], the second player is at the top of the window.
+#[derive(Component, Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Player(pub usize);
+
 /// A marker component to identify walls.
 #[derive(Component)]
 pub struct Wall;
@@ -36,17 +130,71 @@ pub struct Wall;
 #[derive(Component)]
 pub struct BoundingBox(pub Vec2);
 
-/// Game’s score.
+/// Game’s score of a [`Player`].
 #[derive(Component)]
 pub struct Score(pub i32);
 
-/// Identify the text displaying the game’s score and the corresponding section index.
+/// A marker component to identify the root node of the HUD.
+#[derive(Component)]
+pub struct HudRoot;
+
+/// Values displayed by the HUD.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HudValue {
+    /// Game’s score.
+    Score,
+    /// Best score since the game started.
+    HighScore,
+    /// Level number and name.
+    Level,
+    /// Elapsed time since the beginning of the session.
+    Time,
+    /// Active effects and their remaining time.
+    Effects,
+    /// Banner in the middle of the playfield, see [`super::resources::HudState`].
+    Banner,
+}
+
+/// Identify a HUD text, the value it displays and the corresponding section index.
 #[derive(Component)]
-pub struct ScoreText {
-    /// The section index displaying the score.
+pub struct HudText {
+    /// The value displayed.
+    pub value: HudValue,
+    /// The section index displaying the value.
     pub section: usize,
 }
 
+/// A marker component to identify the root node of the settings menu.
+#[derive(Component)]
+pub struct SettingsMenuRoot;
+
+/// Icon of a remaining life in the HUD, visible if the index is lower than the remaining lives.
+#[derive(Component)]
+pub struct LifeIcon(pub u32);
+
 /// Velocity to apply to [`bevy::prelude::Transform`] at [`super::GameStage::Move`] stage.
 #[derive(Component, Default, Clone, Copy)]
 pub struct Velocity(pub Vec2);
+
+/// A short-lived visual particle, despawned at the end of its lifetime.
+#[derive(Component)]
+pub struct Particle {
+    /// Particle fades out along this timer.
+    pub lifetime: Timer,
+}
+
+/// Recent positions of a ball, the most recent first.
+#[derive(Component, Default)]
+pub struct Trail {
+    /// Positions recorded at [`super::GameStage::Ui`] stage.
+    pub positions: VecDeque<Vec2>,
+}
+
+/// A ghost circle drawn at one of the recent positions of a ball.
+#[derive(Component)]
+pub struct TrailGhost {
+    /// The ball with the [`Trail`].
+    pub ball: Entity,
+    /// Index of the position in [`Trail::positions`].
+    pub index: usize,
+}
@@ -0,0 +1,68 @@
+//! Player controls.
+//!
+//! The gameplay systems read the [`PlayerInputs`] of the frame rather than the keyboard,
+//! so the inputs of a remote player can be played too, see [`super::net`].
+//!
+//!  - Insert a [`PlayerInputs`] resource.
+//!  - Read the keyboard into the [`PlayerInputs`] of the local players at [`CoreStage::PreUpdate`] stage.
+//!    A launch is kept until a tick of the simulation plays it, frames may run without ticks.
+//!  - Forget the played launches at [`GameStage::Move`] stage.
+
+use super::{
+    components::Player,
+    resources::{GameConfig, PlayerInput, PlayerInputs},
+    GameStage,
+};
+use bevy::{input::InputSystem, prelude::*};
+use std::mem;
+
+/// Player controls as a Bevy’s plugin.
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PlayerInputs::default())
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                keyboard_input_system.after(InputSystem),
+            )
+            .add_system_to_stage(GameStage::Move, played_launch_system);
+    }
+}
+
+/// Input of `player` on the keyboard, with the key bindings of the first or the second player.
+pub fn keyboard_input(keys: &Input<KeyCode>, player: Player, cfg: &GameConfig) -> PlayerInput {
+    let (left, right) = cfg.key_bindings.paddle_keys(player);
+    // Only the first player aims
+    let aiming = player.0 == 0;
+
+    PlayerInput {
+        left: keys.pressed(left),
+        right: keys.pressed(right),
+        launch: keys.just_pressed(cfg.key_bindings.launch_key(player)),
+        aim_left: aiming && keys.pressed(cfg.key_bindings.aim_left),
+        aim_right: aiming && keys.pressed(cfg.key_bindings.aim_right),
+    }
+}
+
+/// Also public to replace the inputs of the remote players after it.
+pub fn keyboard_input_system(
+    keys: Res<Input<KeyCode>>,
+    mut inputs: ResMut<PlayerInputs>,
+    cfg: Res<GameConfig>,
+) {
+    let previous = mem::take(&mut inputs.0);
+    inputs.0 = (0..cfg.game_mode.players())
+        .map(|player| {
+            let mut input = keyboard_input(&keys, Player(player), &cfg);
+            input.launch |= previous.get(player).map_or(false, |input| input.launch);
+            input
+        })
+        .collect();
+}
+
+fn played_launch_system(mut inputs: ResMut<PlayerInputs>) {
+    for input in inputs.0.iter_mut() {
+        input.launch = false;
+    }
+}
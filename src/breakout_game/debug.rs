@@ -0,0 +1,336 @@
+//! Debug overlay, only built with the `debug` cargo feature.
+//!
+//! The overlay is toggled with [`KeyBindings::debug_overlay`]. It draws the outline of every [`BoundingBox`],
+//! the [`Velocity`] vectors as arrows and the recent [`BallContact`]s with their [`Collision`] side.
+//! It shows the FPS, the entity counts and the time spent in each [`GameStage`].
+//! While it is shown, [`KeyBindings::debug_pause`] pauses the game and [`KeyBindings::debug_step`]
+//! runs a single frame while paused.
+//!
+//!  - Insert a [`DebugOverlay`] resource.
+//!  - Measure the time spent in each [`GameStage`], from its start to its end.
+//!  - Process the debug keys at [`CoreStage::PreUpdate`] stage.
+//!  - Collect the contacts and redraw the overlay at [`GameStage::Ui`] stage.
+//!  - Pause the game again after a single frame at [`CoreStage::Last`] stage.
+//!
+//! [`KeyBindings::debug_overlay`]: super::resources::KeyBindings::debug_overlay
+//! [`KeyBindings::debug_pause`]: super::resources::KeyBindings::debug_pause
+//! [`KeyBindings::debug_step`]: super::resources::KeyBindings::debug_step
+
+use super::{
+    components::{Ball, BoundingBox, Brick, Velocity},
+    events::BallContact,
+    resources::{GameAssets, GameConfig, Paused},
+    simulation_clock_system, GameStage,
+};
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    input::InputSystem,
+    prelude::*,
+    sprite::collide_aabb::Collision,
+};
+use bevy_prototype_lyon::prelude::*;
+use std::time::{Duration, Instant};
+
+/// All the [`GameStage`]s, with their name.
+const STAGES: [(&str, GameStage); 7] = [
+    ("Init", GameStage::Init),
+    ("Input", GameStage::Input),
+    ("Move", GameStage::Move),
+    ("Paddle", GameStage::Paddle),
+    ("Ball", GameStage::Ball),
+    ("Events", GameStage::Events),
+    ("Ui", GameStage::Ui),
+];
+
+/// Position of the overlay along Z-axis, above everything.
+const OVERLAY_Z: f32 = 10.;
+
+/// Duration a velocity arrow represents, its length is the distance traveled meanwhile.
+const VELOCITY_ARROW_TIME: f32 = 0.2;
+
+/// Duration a contact remains shown, while the game runs.
+const CONTACT_DURATION: f32 = 1.;
+
+/// Maximum number of contacts shown, the oldest ones are forgotten.
+const MAX_CONTACTS: usize = 64;
+
+/// Debug overlay logic as a Bevy’s plugin.
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(FrameTimeDiagnosticsPlugin)
+            .insert_resource(DebugOverlay::default())
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                debug_input_system
+                    .after(InputSystem)
+                    .before(simulation_clock_system),
+            )
+            .add_system_to_stage(GameStage::Ui, collect_contacts_system)
+            .add_system_to_stage(
+                GameStage::Ui,
+                debug_view_system.after(collect_contacts_system),
+            )
+            .add_system_to_stage(CoreStage::Last, end_step_system);
+
+        for (index, (_, stage)) in STAGES.into_iter().enumerate() {
+            app.add_system_to_stage(stage, stage_start_system(index).at_start())
+                .add_system_to_stage(stage, stage_end_system(index).at_end());
+        }
+    }
+}
+
+/// A marker component to identify the entities of the debug overlay.
+#[derive(Component)]
+pub struct DebugRoot;
+
+/// State of the debug overlay.
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    /// If true, the overlay is shown.
+    pub open: bool,
+    /// If true, the game runs a single frame and is paused again.
+    pub step: bool,
+    /// Start of the [`GameStage`]s in the current frame, see [`STAGES`].
+    stage_starts: [Option<Instant>; STAGES.len()],
+    /// Smoothed time spent in the [`GameStage`]s, see [`STAGES`].
+    stage_times: [Duration; STAGES.len()],
+    /// Recent contacts, with their remaining time.
+    contacts: Vec<(Vec2, Collision, Timer)>,
+}
+
+/// Record the start of the stage at `index` in [`STAGES`].
+fn stage_start_system(index: usize) -> impl FnMut(&mut World) + Send + Sync + 'static {
+    move |world: &mut World| {
+        world.resource_mut::<DebugOverlay>().stage_starts[index] = Some(Instant::now());
+    }
+}
+
+/// Measure the time spent in the stage at `index` in [`STAGES`].
+fn stage_end_system(index: usize) -> impl FnMut(&mut World) + Send + Sync + 'static {
+    move |world: &mut World| {
+        let mut overlay = world.resource_mut::<DebugOverlay>();
+        if let Some(start) = overlay.stage_starts[index].take() {
+            let time = &mut overlay.stage_times[index];
+            // Smoothed over a few frames to be readable
+            *time = time.mul_f32(0.9) + start.elapsed().mul_f32(0.1);
+        }
+    }
+}
+
+fn debug_input_system(
+    keys: Res<Input<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut paused: ResMut<Paused>,
+    cfg: Res<GameConfig>,
+) {
+    if keys.just_pressed(cfg.key_bindings.debug_overlay) {
+        overlay.open = !overlay.open;
+    }
+
+    if !overlay.open {
+        return;
+    }
+
+    if keys.just_pressed(cfg.key_bindings.debug_pause) {
+        paused.0 = !paused.0;
+    } else if paused.0 && keys.just_pressed(cfg.key_bindings.debug_step) {
+        paused.0 = false;
+        overlay.step = true;
+    }
+}
+
+fn end_step_system(mut overlay: ResMut<DebugOverlay>, mut paused: ResMut<Paused>) {
+    if overlay.step {
+        overlay.step = false;
+        paused.0 = true;
+    }
+}
+
+fn collect_contacts_system(
+    mut ball_contact_events: EventReader<BallContact>,
+    mut overlay: ResMut<DebugOverlay>,
+    paused: Res<Paused>,
+    time: Res<Time>,
+) {
+    // Kept while paused, to look at them frame by frame
+    if !paused.0 {
+        for (_, _, timer) in overlay.contacts.iter_mut() {
+            timer.tick(time.delta());
+        }
+        overlay.contacts.retain(|(_, _, timer)| !timer.finished());
+    }
+
+    for event in ball_contact_events.iter() {
+        overlay.contacts.push((
+            event.position,
+            event.side,
+            Timer::from_seconds(CONTACT_DURATION, TimerMode::Once),
+        ));
+    }
+    let excess = overlay.contacts.len().saturating_sub(MAX_CONTACTS);
+    overlay.contacts.drain(..excess);
+}
+
+/// Arrow from `start` to `end`, with its head at `end`.
+fn arrow(path: &mut PathBuilder, start: Vec2, end: Vec2) {
+    let head = (start - end).clamp_length_max(6.);
+    path.move_to(start);
+    path.line_to(end);
+    path.move_to(end + Vec2::from_angle(0.5).rotate(head));
+    path.line_to(end);
+    path.line_to(end + Vec2::from_angle(-0.5).rotate(head));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn debug_view_system(
+    mut commands: Commands,
+    overlay: Res<DebugOverlay>,
+    root_query: Query<Entity, With<DebugRoot>>,
+    box_query: Query<(&GlobalTransform, &BoundingBox)>,
+    velocity_query: Query<(&GlobalTransform, &Velocity)>,
+    entity_query: Query<(), Without<DebugRoot>>,
+    count_query: Query<(Option<&Brick>, Option<&Ball>)>,
+    diagnostics: Res<Diagnostics>,
+    paused: Res<Paused>,
+    game_assets: Res<GameAssets>,
+    cfg: Res<GameConfig>,
+) {
+    // Redrawn every frame, everything may move
+    for root in root_query.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+
+    if !overlay.open {
+        return;
+    }
+
+    let mut boxes = PathBuilder::new();
+    for (tf, bounding_box) in box_query.iter() {
+        let center = tf.translation().truncate();
+        let half = bounding_box.0 / 2.;
+        boxes.move_to(center - half);
+        boxes.line_to(center + Vec2::new(half.x, -half.y));
+        boxes.line_to(center + half);
+        boxes.line_to(center + Vec2::new(-half.x, half.y));
+        boxes.close();
+    }
+    commands
+        .spawn(GeometryBuilder::build_as(
+            &boxes.build(),
+            DrawMode::Stroke(StrokeMode::new(Color::LIME_GREEN, 1.)),
+            Transform::from_xyz(0., 0., OVERLAY_Z),
+        ))
+        .insert(DebugRoot);
+
+    let mut arrows = PathBuilder::new();
+    for (tf, velocity) in velocity_query.iter() {
+        if velocity.0 != Vec2::ZERO {
+            let start = tf.translation().truncate();
+            arrow(&mut arrows, start, start + velocity.0 * VELOCITY_ARROW_TIME);
+        }
+    }
+    commands
+        .spawn(GeometryBuilder::build_as(
+            &arrows.build(),
+            DrawMode::Stroke(StrokeMode::new(Color::YELLOW, 1.)),
+            Transform::from_xyz(0., 0., OVERLAY_Z),
+        ))
+        .insert(DebugRoot);
+
+    let style = TextStyle {
+        font: game_assets.score_font.clone(),
+        font_size: cfg.hud_font_size * cfg.ui_scale * 0.7,
+        color: Color::WHITE,
+    };
+
+    // A cross at each contact, named after the side of the collider
+    let mut crosses = PathBuilder::new();
+    for (position, side, _) in overlay.contacts.iter() {
+        crosses.move_to(*position - Vec2::splat(3.));
+        crosses.line_to(*position + Vec2::splat(3.));
+        crosses.move_to(*position + Vec2::new(-3., 3.));
+        crosses.line_to(*position + Vec2::new(3., -3.));
+        commands
+            .spawn(Text2dBundle {
+                text: Text::from_section(
+                    format!("{:?}", side),
+                    TextStyle {
+                        color: Color::RED,
+                        ..style.clone()
+                    },
+                ),
+                transform: Transform::from_translation(
+                    (*position + Vec2::new(0., 12.)).extend(OVERLAY_Z),
+                ),
+                ..Default::default()
+            })
+            .insert(DebugRoot);
+    }
+    commands
+        .spawn(GeometryBuilder::build_as(
+            &crosses.build(),
+            DrawMode::Stroke(StrokeMode::new(Color::RED, 2.)),
+            Transform::from_xyz(0., 0., OVERLAY_Z),
+        ))
+        .insert(DebugRoot);
+
+    // Statistics at the bottom right of the window
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(0.);
+    let (bricks, balls) = count_query
+        .iter()
+        .fold((0, 0), |(bricks, balls), (brick, ball)| {
+            (
+                bricks + brick.is_some() as usize,
+                balls + ball.is_some() as usize,
+            )
+        });
+    let mut lines = vec![
+        format!("FPS {:.0}", fps),
+        format!(
+            "Entities {} ({} bricks, {} balls)",
+            entity_query.iter().count(),
+            bricks,
+            balls
+        ),
+    ];
+    lines.extend(
+        STAGES
+            .iter()
+            .zip(overlay.stage_times.iter())
+            .map(|((name, _), time)| format!("{} {} µs", name, time.as_micros())),
+    );
+    if paused.0 {
+        lines.push(format!(
+            "Paused, {:?} steps a frame",
+            cfg.key_bindings.debug_step
+        ));
+    }
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(cfg.wall_thickness * 2.),
+                    bottom: Val::Px(cfg.wall_thickness * 2.),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                ..Default::default()
+            },
+            z_index: ZIndex::Global(2),
+            ..Default::default()
+        })
+        .insert(DebugRoot)
+        .with_children(|parent| {
+            for line in lines {
+                parent.spawn(TextBundle::from_section(line, style.clone()));
+            }
+        });
+}
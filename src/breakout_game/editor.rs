@@ -0,0 +1,472 @@
+//! Level editor related stuff.
+//!
+//! The editor is toggled with [`KeyBindings::editor`] in debug builds, or when the game is started
+//! with the `--editor` flag (see [`GameConfig::level_editor`]). The game is paused while it is open.
+//! It shows the grid of the brick’s spawning area, bricks are painted with the left mouse button
+//! and erased with the right one. Other actions use the keyboard:
+//!
+//!  - `K` cycles the hit points of the painted bricks, `C` their color.
+//!  - `[` and `]` remove or add a column, `-` and `=` a row.
+//!  - `Delete` erases all the bricks.
+//!  - `Ctrl+Z` undoes, `Ctrl+Y` or `Ctrl+Shift+Z` redoes.
+//!  - `Ctrl+S` saves and `Ctrl+O` loads the level file at [`GameConfig::editor_level_path`].
+//!  - `Enter` test-plays the level, it becomes the [`CustomLevel`] of the game
+//!    until it is cleared, the game is over or the editor is opened again.
+//!
+//! Systems:
+//!
+//!  - Insert [`Editor`] resource at [`StartupStage::Startup`] stage.
+//!  - Process editor input at [`GameStage::Ui`] stage.
+//!  - (Re)spawn the grid, the bricks and the texts of the editor when it changes at [`GameStage::Ui`] stage.
+//!  - End the test-play on [`LevelCleared`] or [`GameOver`] at [`GameStage::Events`] stage.
+//!
+//! [`KeyBindings::editor`]: super::resources::KeyBindings::editor
+
+use super::{
+    brick::{cell_translation, position_cell},
+    components::{Brick, EditorRoot},
+    events::{GameOver, LevelCleared},
+    level::{DesignBrick, LevelDesign, MAX_AREA_SIZE},
+    resources::{
+        ActiveTheme, BrickRespawn, CustomLevel, Editor, GameAssets, GameConfig, Localization,
+        Paused,
+    },
+    GameStage,
+};
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use std::path::Path;
+
+/// Level editor logic as a Bevy’s plugin.
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(init_editor_system)
+            .add_system_to_stage(GameStage::Ui, editor_input_system)
+            .add_system_to_stage(GameStage::Ui, editor_view_system.after(editor_input_system))
+            .add_system_to_stage(GameStage::Events, end_test_play_system);
+    }
+}
+
+impl Editor {
+    /// Closed editor with an empty level of the brick’s spawning area of `cfg`.
+    pub fn new(cfg: &GameConfig) -> Editor {
+        Editor {
+            open: false,
+            design: LevelDesign::empty(cfg),
+            hit_points: 1,
+            color: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            stroke: None,
+            message: String::new(),
+        }
+    }
+
+    /// Keep the current design to undo the next change, the undone changes are lost.
+    pub fn record(&mut self) {
+        let design = self.design.clone();
+        self.undo.push(design);
+        self.redo.clear();
+    }
+
+    /// Restore the design before the last change.
+    pub fn undo(&mut self) {
+        if let Some(design) = self.undo.pop() {
+            let undone = std::mem::replace(&mut self.design, design);
+            self.redo.push(undone);
+        }
+    }
+
+    /// Restore the last undone change.
+    pub fn redo(&mut self) {
+        if let Some(design) = self.redo.pop() {
+            let redone = std::mem::replace(&mut self.design, design);
+            self.undo.push(redone);
+        }
+    }
+}
+
+fn init_editor_system(mut commands: Commands, mut paused: ResMut<Paused>, cfg: Res<GameConfig>) {
+    let mut editor = Editor::new(&cfg);
+    editor.open = cfg.level_editor;
+    paused.0 |= editor.open;
+    commands.insert_resource(editor);
+}
+
+/// Cell under the mouse cursor in the edited level, if any.
+fn cursor_cell(windows: &Windows, design: &LevelDesign, cfg: &GameConfig) -> Option<(u32, u32)> {
+    let window = windows.get_primary()?;
+    // The camera is centered on the window
+    let position = window.cursor_position()? - Vec2::new(window.width(), window.height()) / 2.;
+    position_cell(position, design.cols, design.rows, cfg)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn editor_input_system(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut editor: ResMut<Editor>,
+    mut paused: ResMut<Paused>,
+    mut custom_level: ResMut<CustomLevel>,
+    mut brick_respawn: ResMut<BrickRespawn>,
+    bricks_query: Query<Entity, With<Brick>>,
+    theme: Res<ActiveTheme>,
+    localization: Res<Localization>,
+    cfg: Res<GameConfig>,
+) {
+    let enabled = cfg!(debug_assertions) || cfg.level_editor;
+    if enabled && keys.just_pressed(cfg.key_bindings.editor) {
+        editor.open = !editor.open;
+        paused.0 = editor.open;
+        if editor.open {
+            custom_level.0 = None;
+        }
+    }
+
+    if !editor.open {
+        return;
+    }
+    // Also paused when the settings menu is closed
+    if !paused.0 {
+        paused.0 = true;
+    }
+
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    let path = Path::new(&cfg.editor_level_path);
+
+    if ctrl && (keys.just_pressed(KeyCode::Y) || (shift && keys.just_pressed(KeyCode::Z))) {
+        editor.redo();
+    } else if ctrl && keys.just_pressed(KeyCode::Z) {
+        editor.undo();
+    } else if ctrl && keys.just_pressed(KeyCode::S) {
+        let path_arg = [("path", cfg.editor_level_path.as_str())];
+        editor.message = match editor.design.save(path) {
+            Ok(()) => localization.format("editor-saved", &path_arg),
+            Err(err) => localization.format("editor-error", &[("error", &err.to_string())]),
+        };
+    } else if ctrl && keys.just_pressed(KeyCode::O) {
+        let path_arg = [("path", cfg.editor_level_path.as_str())];
+        editor.message = match LevelDesign::load(path) {
+            Ok(design) => {
+                editor.record();
+                editor.design = design;
+                localization.format("editor-loaded", &path_arg)
+            }
+            Err(err) => localization.format("editor-error", &[("error", &err.to_string())]),
+        };
+    } else if keys.just_pressed(KeyCode::Delete) && !editor.design.bricks.is_empty() {
+        editor.record();
+        editor.design.bricks.clear();
+    } else if keys.just_pressed(KeyCode::K) {
+        let max = cfg.difficulty_curve.brick_hit_points_max.max(1);
+        editor.hit_points = editor.hit_points % max + 1;
+    } else if keys.just_pressed(KeyCode::C) {
+        let len = theme.0.brick_colors().len();
+        editor.color = match editor.color {
+            None if len > 0 => Some(0),
+            Some(index) if index + 1 < len => Some(index + 1),
+            _ => None,
+        };
+    } else if keys.just_pressed(KeyCode::Return) {
+        // Test-play the level from scratch
+        custom_level.0 = Some(editor.design.clone());
+        for entity in bricks_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        brick_respawn.immediate_spawn = true;
+        editor.open = false;
+        paused.0 = false;
+        return;
+    }
+
+    let (mut cols, mut rows) = (editor.design.cols, editor.design.rows);
+    if keys.just_pressed(KeyCode::LBracket) {
+        cols = cols.saturating_sub(1).max(1);
+    } else if keys.just_pressed(KeyCode::RBracket) {
+        cols = (cols + 1).min(MAX_AREA_SIZE);
+    } else if keys.just_pressed(KeyCode::Minus) {
+        rows = rows.saturating_sub(1).max(1);
+    } else if keys.just_pressed(KeyCode::Equals) {
+        rows = (rows + 1).min(MAX_AREA_SIZE);
+    }
+    if (cols, rows) != (editor.design.cols, editor.design.rows) {
+        editor.record();
+        editor.design.resize(cols, rows);
+    }
+
+    // A stroke, from press to release, is undone at once
+    if mouse_buttons.any_just_pressed([MouseButton::Left, MouseButton::Right]) {
+        editor.stroke = Some(editor.design.clone());
+    }
+
+    if let Some((col, row)) = cursor_cell(&windows, &editor.design, &cfg) {
+        if mouse_buttons.pressed(MouseButton::Left) {
+            let brick = DesignBrick {
+                col,
+                row,
+                hit_points: editor.hit_points,
+                color: editor
+                    .color
+                    .and_then(|index| theme.0.brick_colors().get(index).copied()),
+            };
+            // Only mutate on actual changes, to respawn the view only when needed
+            if editor.design.brick(col, row) != Some(&brick) {
+                editor.design.paint(brick);
+            }
+        } else if mouse_buttons.pressed(MouseButton::Right)
+            && editor.design.brick(col, row).is_some()
+        {
+            editor.design.erase(col, row);
+        }
+    }
+
+    if mouse_buttons.any_just_released([MouseButton::Left, MouseButton::Right]) {
+        if let Some(stroke) = editor.stroke.take() {
+            if stroke != editor.design {
+                editor.undo.push(stroke);
+                editor.redo.clear();
+            }
+        }
+    }
+}
+
+/// The game goes on with the generated levels after a test-play.
+fn end_test_play_system(
+    mut level_cleared_events: EventReader<LevelCleared>,
+    mut game_over_events: EventReader<GameOver>,
+    mut custom_level: ResMut<CustomLevel>,
+) {
+    let cleared = level_cleared_events.iter().count() > 0;
+    let game_over = game_over_events.iter().count() > 0;
+    if (cleared || game_over) && custom_level.0.is_some() {
+        custom_level.0 = None;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn editor_view_system(
+    mut commands: Commands,
+    editor: Res<Editor>,
+    root_query: Query<Entity, With<EditorRoot>>,
+    mut bricks_query: Query<&mut Visibility, With<Brick>>,
+    game_assets: Res<GameAssets>,
+    theme: Res<ActiveTheme>,
+    localization: Res<Localization>,
+    cfg: Res<GameConfig>,
+) {
+    if !editor.is_changed() && !theme.is_changed() && !localization.is_changed() {
+        return;
+    }
+
+    for root in root_query.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+    // The edited level replaces the bricks of the game
+    for mut visibility in bricks_query.iter_mut() {
+        visibility.is_visible = !editor.open;
+    }
+
+    if !editor.open {
+        return;
+    }
+
+    let design = &editor.design;
+    let theme = &theme.0;
+
+    // Grid of the brick’s spawning area
+    let mut grid = PathBuilder::new();
+    let corner =
+        cell_translation(0, 0, design.cols, design.rows, &cfg).truncate() - cfg.brick_size / 2.;
+    let size = Vec2::new(design.cols as f32, design.rows as f32) * cfg.brick_size;
+    for col in 0..=design.cols {
+        let x = corner.x + col as f32 * cfg.brick_size.x;
+        grid.move_to(Vec2::new(x, corner.y));
+        grid.line_to(Vec2::new(x, corner.y + size.y));
+    }
+    for row in 0..=design.rows {
+        let y = corner.y + row as f32 * cfg.brick_size.y;
+        grid.move_to(Vec2::new(corner.x, y));
+        grid.line_to(Vec2::new(corner.x + size.x, y));
+    }
+    commands
+        .spawn(GeometryBuilder::build_as(
+            &grid.build(),
+            DrawMode::Stroke(StrokeMode::new(theme.outline, 1.)),
+            Transform::from_xyz(0., 0., cfg.brick_z + 0.5),
+        ))
+        .insert(EditorRoot);
+
+    let shape = shapes::Rectangle {
+        extents: cfg.brick_size,
+        ..Default::default()
+    };
+    for brick in design.bricks.iter() {
+        let strong_color = theme.strong_brick.filter(|_| brick.hit_points > 1);
+        let color = brick.color.or(strong_color).unwrap_or_else(|| {
+            let colors = theme.brick_colors();
+            // Like the rows of a theme, as random colors can’t be previewed
+            let row_from_top = design.rows.saturating_sub(brick.row + 1) as usize;
+            colors
+                .get(row_from_top % colors.len().max(1))
+                .copied()
+                .unwrap_or(Color::WHITE)
+        });
+        commands
+            .spawn(GeometryBuilder::build_as(
+                &shape,
+                theme.outlined_brick(color, brick.hit_points),
+                Transform::from_translation(cell_translation(
+                    brick.col,
+                    brick.row,
+                    design.cols,
+                    design.rows,
+                    &cfg,
+                )),
+            ))
+            .insert(EditorRoot);
+    }
+
+    // Status and help at the bottom of the window
+    let color = match editor.color {
+        Some(index) => (index + 1).to_string(),
+        None => localization.text("editor-color-theme"),
+    };
+    let status = localization.format(
+        "editor-status",
+        &[
+            ("count", &design.bricks.len().to_string()),
+            ("cols", &design.cols.to_string()),
+            ("rows", &design.rows.to_string()),
+            ("hit_points", &editor.hit_points.to_string()),
+            ("color", &color),
+        ],
+    );
+    let style = TextStyle {
+        font: game_assets.score_font.clone(),
+        font_size: cfg.hud_font_size * cfg.ui_scale * 0.8,
+        color: theme.hud_value,
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(cfg.wall_thickness * 2.),
+                    bottom: Val::Px(cfg.wall_thickness * 2.),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            z_index: ZIndex::Global(1),
+            ..Default::default()
+        })
+        .insert(EditorRoot)
+        .with_children(|parent| {
+            for text in [
+                localization.text("editor-help-paint"),
+                localization.text("editor-help-keys"),
+                localization.text("editor-help-file"),
+                status,
+                editor.message.clone(),
+            ] {
+                parent.spawn(TextBundle::from_section(text, style.clone()));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Brick at `col` and `row` with a single hit point.
+    fn brick(col: u32, row: u32) -> DesignBrick {
+        DesignBrick {
+            col,
+            row,
+            hit_points: 1,
+            color: None,
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_restore_the_designs() {
+        let mut editor = Editor::new(&GameConfig::default());
+        let empty = editor.design.clone();
+
+        editor.record();
+        editor.design.paint(brick(0, 0));
+        let painted = editor.design.clone();
+        editor.record();
+        editor.design.resize(3, 3);
+
+        editor.undo();
+        assert_eq!(editor.design, painted);
+        editor.undo();
+        assert_eq!(editor.design, empty);
+        // Nothing left to undo
+        editor.undo();
+        assert_eq!(editor.design, empty);
+
+        editor.redo();
+        assert_eq!(editor.design, painted);
+        editor.redo();
+        assert_eq!((editor.design.cols, editor.design.rows), (3, 3));
+        // Nothing left to redo
+        editor.redo();
+        assert_eq!((editor.design.cols, editor.design.rows), (3, 3));
+    }
+
+    #[test]
+    fn a_change_forgets_the_undone_changes() {
+        let mut editor = Editor::new(&GameConfig::default());
+
+        editor.record();
+        editor.design.paint(brick(0, 0));
+        editor.undo();
+        editor.record();
+        editor.design.paint(brick(1, 0));
+        let changed = editor.design.clone();
+
+        editor.redo();
+        assert_eq!(editor.design, changed);
+        assert!(editor.design.brick(0, 0).is_none());
+    }
+
+    #[test]
+    fn invalid_level_files_are_not_loaded() {
+        let dir = std::env::temp_dir().join(format!("breakout-editor-{}", std::process::id()));
+        let path = dir.join("level.ron");
+        let mut design = LevelDesign::empty(&GameConfig::default());
+        design.paint(brick(0, 0));
+        design.save(&path).unwrap();
+        assert_eq!(LevelDesign::load(&path).unwrap(), design);
+
+        let out_of_area = LevelDesign {
+            bricks: vec![brick(design.cols, 0)],
+            ..design.clone()
+        };
+        let same_cell = LevelDesign {
+            bricks: vec![brick(0, 0), brick(0, 0)],
+            ..design.clone()
+        };
+        let empty_area = LevelDesign {
+            cols: 0,
+            bricks: Vec::new(),
+            ..design
+        };
+        for invalid in [out_of_area, same_cell, empty_area] {
+            invalid.save(&path).unwrap();
+            let err = LevelDesign::load(&path).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,94 @@
+//! All the gameplay events sent by the game.
+//!
+//! Events are sent by the gameplay systems (collision, reset, spawn)
+//! and processed by independent listeners at [`super::GameStage::Events`] stage.
+
+use super::components::{BrickKind, Player};
+use bevy::{
+    prelude::{Color, Entity, Vec2},
+    sprite::collide_aabb::Collision,
+};
+
+/// Sent when a ball destroys a brick.
+pub struct BrickDestroyed {
+    /// The destroyed brick, already despawned when the event is read.
+    pub entity: Entity,
+    /// The ball which destroyed the brick.
+    pub ball: Entity,
+    /// Position of the brick.
+    pub position: Vec2,
+    /// Kind of the brick.
+    pub kind: BrickKind,
+    /// Fill color of the brick.
+    pub color: Color,
+    /// Velocity of the ball after the bounce.
+    pub velocity: Vec2,
+}
+
+/// Sent when a ball goes out of the window.
+pub struct BallLost {
+    /// The lost ball.
+    pub entity: Entity,
+    /// The player guarding the side where the ball went out, who throws it again.
+    pub player: Player,
+    /// Last position of the ball.
+    pub position: Vec2,
+    /// Velocity of the ball before being lost.
+    pub velocity: Vec2,
+}
+
+/// Sent when a ball bounces on a paddle.
+pub struct PaddleHit {
+    /// The ball bouncing on the paddle.
+    pub ball: Entity,
+    /// The paddle hit by the ball.
+    pub paddle: Entity,
+    /// Position of the ball.
+    pub position: Vec2,
+    /// Velocity of the ball after the bounce.
+    pub velocity: Vec2,
+}
+
+/// Sent when a ball bounces on a wall.
+pub struct WallHit {
+    /// The ball bouncing on the wall.
+    pub ball: Entity,
+    /// The wall hit by the ball.
+    pub wall: Entity,
+    /// Position of the ball.
+    pub position: Vec2,
+    /// Velocity of the ball after the bounce.
+    pub velocity: Vec2,
+}
+
+/// Sent when a ball touches a collider, for each collider it bounces on.
+pub struct BallContact {
+    /// The ball.
+    pub ball: Entity,
+    /// The collider touched by the ball, a brick, a paddle or a wall.
+    pub collider: Entity,
+    /// Position of the ball, moved out of the collider.
+    pub position: Vec2,
+    /// Side of the collider touched by the ball.
+    pub side: Collision,
+}
+
+/// Sent when all the bricks have been destroyed.
+pub struct LevelCleared;
+
+/// Sent when the gameplay state of a tick differs from the remote peer’s, see [`super::net`].
+#[derive(Clone, Debug)]
+pub struct Desync {
+    /// The first tick with a different state.
+    pub tick: u32,
+    /// Hash of the local state.
+    pub local_hash: u64,
+    /// Hash of the remote peer’s state.
+    pub remote_hash: u64,
+}
+
+/// Sent when the last life is lost, or when a player wins in [`super::resources::GameMode::Versus`].
+pub struct GameOver {
+    /// The winner, if any.
+    pub winner: Option<Player>,
+}
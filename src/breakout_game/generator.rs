@@ -0,0 +1,649 @@
+//! Procedural level generators.
+//!
+//! A [`LevelGenerator`] places the bricks of a level in the brick’s spawning area from a seed,
+//! the same seed always gives the same level. The generator of each level is a [`GeneratorKind`]
+//! of the [`DifficultyCurve`], or [`GameConfig::level_generator`] for all levels.
+//! Generators only place bricks in the cells of the level’s [`BrickLayout`] which its [`BrickMovement`] allows.
+//!
+//! Generated levels are constrained by [`generate_level`]: they have at least
+//! [`GameConfig::level_min_bricks`] bricks and, if [`GameConfig::level_reachable`] is set,
+//! every empty cell is reachable by the ball without breaking bricks.
+//!
+//! [`DifficultyCurve`]: super::level::DifficultyCurve
+//! [`BrickLayout`]: super::level::BrickLayout
+//! [`BrickMovement`]: super::level::BrickMovement
+
+use super::{
+    level::{DesignBrick, LevelDesign, LevelParams},
+    resources::GameConfig,
+};
+use rand::{seq::SliceRandom, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+/// Generated levels with too few bricks are generated again with another seed, up to this number of times.
+const MAX_ATTEMPTS: u64 = 8;
+
+/// Places the bricks of a level.
+pub trait LevelGenerator {
+    /// Bricks of an area of `cols` by `rows` for a level of `params`, drawn from `rng`.
+    fn generate(
+        &self,
+        params: &LevelParams,
+        cols: u32,
+        rows: u32,
+        rng: &mut dyn RngCore,
+    ) -> LevelDesign;
+}
+
+/// The available generators and their parameters, as written in the configuration.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum GeneratorKind {
+    /// See [`NoiseGenerator`].
+    Noise,
+    /// See [`MirrorNoiseGenerator`].
+    MirrorNoise,
+    /// See [`CaveGenerator`].
+    Caves {
+        /// Initial probability of a cell to have a brick.
+        fill: f64,
+        /// Number of smoothing steps.
+        steps: u32,
+    },
+    /// See [`TextGenerator`].
+    Text {
+        /// Words to pick from, only letters, digits, spaces and `!` are drawn.
+        words: Vec<String>,
+    },
+    /// See [`ConcentricGenerator`].
+    Concentric {
+        /// Shape of the rings.
+        shape: RingShape,
+    },
+    /// See [`RowsGenerator`].
+    Rows,
+}
+
+impl GeneratorKind {
+    /// The generator with these parameters.
+    pub fn generator(&self) -> Box<dyn LevelGenerator> {
+        match self {
+            GeneratorKind::Noise => Box::new(NoiseGenerator),
+            GeneratorKind::MirrorNoise => Box::new(MirrorNoiseGenerator),
+            GeneratorKind::Caves { fill, steps } => Box::new(CaveGenerator {
+                fill: *fill,
+                steps: *steps,
+            }),
+            GeneratorKind::Text { words } => Box::new(TextGenerator {
+                words: words.clone(),
+            }),
+            GeneratorKind::Concentric { shape } => Box::new(ConcentricGenerator { shape: *shape }),
+            GeneratorKind::Rows => Box::new(RowsGenerator),
+        }
+    }
+}
+
+/// Brick at `col` and `row` of a level of `params` with `rows` rows.
+///
+/// The top row has the most hit points, one less for each row below.
+fn design_brick(params: &LevelParams, col: u32, row: u32, rows: u32) -> DesignBrick {
+    let row_from_top = rows.saturating_sub(row + 1);
+    DesignBrick {
+        col,
+        row,
+        hit_points: params.brick_hit_points.saturating_sub(row_from_top).max(1),
+        color: None,
+    }
+}
+
+/// Level with a brick in each cell of the layout allowed by the movement of an area of `cols` by `rows`
+/// where `has_brick` is true.
+fn design_from_cells(
+    params: &LevelParams,
+    cols: u32,
+    rows: u32,
+    mut has_brick: impl FnMut(u32, u32) -> bool,
+) -> LevelDesign {
+    let mut design = LevelDesign {
+        cols,
+        rows,
+        bricks: Vec::new(),
+        movement: None,
+    };
+
+    for col in 0..cols {
+        for row in 0..rows {
+            if params.layout.contains(col, row, cols, rows)
+                && params.movement.allows(col, row)
+                && has_brick(col, row)
+            {
+                design.bricks.push(design_brick(params, col, row, rows));
+            }
+        }
+    }
+
+    design
+}
+
+/// An independent coin flip per cell of the level’s [`BrickLayout`](super::level::BrickLayout),
+/// with the level’s brick spawn probability.
+pub struct NoiseGenerator;
+
+impl LevelGenerator for NoiseGenerator {
+    fn generate(
+        &self,
+        params: &LevelParams,
+        cols: u32,
+        rows: u32,
+        rng: &mut dyn RngCore,
+    ) -> LevelDesign {
+        design_from_cells(params, cols, rows, |_, _| {
+            rng.gen_bool(params.brick_spawn_probability)
+        })
+    }
+}
+
+/// Like [`NoiseGenerator`] on the left half, mirrored on the right half.
+pub struct MirrorNoiseGenerator;
+
+impl LevelGenerator for MirrorNoiseGenerator {
+    fn generate(
+        &self,
+        params: &LevelParams,
+        cols: u32,
+        rows: u32,
+        rng: &mut dyn RngCore,
+    ) -> LevelDesign {
+        let half = (cols + 1) / 2;
+        let left: Vec<bool> = (0..half * rows)
+            .map(|index| {
+                let (col, row) = (index / rows, index % rows);
+                params.layout.contains(col, row, cols, rows)
+                    && rng.gen_bool(params.brick_spawn_probability)
+            })
+            .collect();
+
+        design_from_cells(params, cols, rows, |col, row| {
+            let col = col.min(cols - 1 - col);
+            left[(col * rows + row) as usize]
+        })
+    }
+}
+
+/// Caves dug by a cellular automaton: random cells are smoothed,
+/// a cell gets a brick when most of its neighbors have one.
+pub struct CaveGenerator {
+    /// Initial probability of a cell to have a brick.
+    pub fill: f64,
+    /// Number of smoothing steps.
+    pub steps: u32,
+}
+
+impl LevelGenerator for CaveGenerator {
+    fn generate(
+        &self,
+        params: &LevelParams,
+        cols: u32,
+        rows: u32,
+        rng: &mut dyn RngCore,
+    ) -> LevelDesign {
+        let (w, h) = (cols as i32, rows as i32);
+        let mut cells: Vec<bool> = (0..w * h)
+            .map(|_| rng.gen_bool(self.fill.clamp(0., 1.)))
+            .collect();
+
+        for _ in 0..self.steps {
+            let previous = cells.clone();
+            // Outside of the area is empty, to keep the caves open
+            let has_brick = |x: i32, y: i32| {
+                x >= 0 && y >= 0 && x < w && y < h && previous[(y * w + x) as usize]
+            };
+
+            for y in 0..h {
+                for x in 0..w {
+                    let neighbors = (-1..=1)
+                        .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                        .filter(|&(dx, dy)| (dx, dy) != (0, 0) && has_brick(x + dx, y + dy))
+                        .count();
+                    cells[(y * w + x) as usize] =
+                        neighbors >= 5 || (neighbors >= 4 && has_brick(x, y));
+                }
+            }
+        }
+
+        design_from_cells(params, cols, rows, |col, row| {
+            cells[(row * cols + col) as usize]
+        })
+    }
+}
+
+/// Width of a glyph of [`FONT`] in cells.
+const GLYPH_WIDTH: u32 = 3;
+/// Height of a glyph of [`FONT`] in cells.
+const GLYPH_HEIGHT: u32 = 5;
+
+/// Tiny font, each glyph is 5 rows from top to bottom of 3 bits from left to right.
+const FONT: [(char, [u8; 5]); 38] = [
+    ('A', [0b010, 0b101, 0b111, 0b101, 0b101]),
+    ('B', [0b110, 0b101, 0b110, 0b101, 0b110]),
+    ('C', [0b011, 0b100, 0b100, 0b100, 0b011]),
+    ('D', [0b110, 0b101, 0b101, 0b101, 0b110]),
+    ('E', [0b111, 0b100, 0b110, 0b100, 0b111]),
+    ('F', [0b111, 0b100, 0b110, 0b100, 0b100]),
+    ('G', [0b011, 0b100, 0b101, 0b101, 0b011]),
+    ('H', [0b101, 0b101, 0b111, 0b101, 0b101]),
+    ('I', [0b111, 0b010, 0b010, 0b010, 0b111]),
+    ('J', [0b001, 0b001, 0b001, 0b101, 0b010]),
+    ('K', [0b101, 0b101, 0b110, 0b101, 0b101]),
+    ('L', [0b100, 0b100, 0b100, 0b100, 0b111]),
+    ('M', [0b101, 0b111, 0b111, 0b101, 0b101]),
+    ('N', [0b110, 0b101, 0b101, 0b101, 0b101]),
+    ('O', [0b010, 0b101, 0b101, 0b101, 0b010]),
+    ('P', [0b110, 0b101, 0b110, 0b100, 0b100]),
+    ('Q', [0b010, 0b101, 0b101, 0b110, 0b011]),
+    ('R', [0b110, 0b101, 0b110, 0b101, 0b101]),
+    ('S', [0b011, 0b100, 0b010, 0b001, 0b110]),
+    ('T', [0b111, 0b010, 0b010, 0b010, 0b010]),
+    ('U', [0b101, 0b101, 0b101, 0b101, 0b111]),
+    ('V', [0b101, 0b101, 0b101, 0b101, 0b010]),
+    ('W', [0b101, 0b101, 0b111, 0b111, 0b101]),
+    ('X', [0b101, 0b101, 0b010, 0b101, 0b101]),
+    ('Y', [0b101, 0b101, 0b010, 0b010, 0b010]),
+    ('Z', [0b111, 0b001, 0b010, 0b100, 0b111]),
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b110, 0b001, 0b010, 0b100, 0b111]),
+    ('3', [0b110, 0b001, 0b010, 0b001, 0b110]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b110, 0b001, 0b110]),
+    ('6', [0b011, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b010, 0b010, 0b010]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b110]),
+    ('!', [0b010, 0b010, 0b010, 0b000, 0b010]),
+    (' ', [0b000, 0b000, 0b000, 0b000, 0b000]),
+];
+
+/// A word picked at random written in bricks, wrapped on several lines if needed.
+///
+/// Each glyph takes 3 by 5 cells, the word is picked among the words which fit in the area.
+/// If none fits, the glyphs which don’t fit are dropped.
+pub struct TextGenerator {
+    /// Words to pick from.
+    pub words: Vec<String>,
+}
+
+impl LevelGenerator for TextGenerator {
+    fn generate(
+        &self,
+        params: &LevelParams,
+        cols: u32,
+        rows: u32,
+        rng: &mut dyn RngCore,
+    ) -> LevelDesign {
+        // One cell between glyphs and between lines
+        let per_line = ((cols + 1) / (GLYPH_WIDTH + 1)).max(1) as usize;
+        let max_lines = ((rows + 1) / (GLYPH_HEIGHT + 1)) as usize;
+
+        let fitting: Vec<&String> = self
+            .words
+            .iter()
+            .filter(|word| word.chars().count() <= per_line * max_lines)
+            .collect();
+        let text = match fitting.choose(&mut *rng) {
+            Some(word) => word.as_str(),
+            None => self.words.choose(rng).map_or("", String::as_str),
+        };
+        let glyphs: Vec<[u8; 5]> = text
+            .to_uppercase()
+            .chars()
+            .map(|c| {
+                FONT.iter()
+                    .find(|(glyph, _)| *glyph == c)
+                    .map_or([0; 5], |glyph| glyph.1)
+            })
+            .collect();
+        let lines: Vec<&[[u8; 5]]> = glyphs.chunks(per_line).take(max_lines).collect();
+
+        let text_height = (lines.len() as u32 * (GLYPH_HEIGHT + 1)).saturating_sub(1);
+        let top = rows - (rows - text_height.min(rows)) / 2;
+
+        design_from_cells(params, cols, rows, |col, row| {
+            let from_top = top.saturating_sub(row + 1);
+            let (line, glyph_row) = (
+                (from_top / (GLYPH_HEIGHT + 1)) as usize,
+                from_top % (GLYPH_HEIGHT + 1),
+            );
+            let line = match lines.get(line) {
+                Some(line) if row < top && glyph_row < GLYPH_HEIGHT => line,
+                _ => return false,
+            };
+
+            // Each line is centered
+            let line_width = (line.len() as u32 * (GLYPH_WIDTH + 1)).saturating_sub(1);
+            let left = cols.saturating_sub(line_width) / 2;
+            let x = match col.checked_sub(left) {
+                Some(x) if x < line_width => x,
+                _ => return false,
+            };
+            let (glyph, glyph_col) = ((x / (GLYPH_WIDTH + 1)) as usize, x % (GLYPH_WIDTH + 1));
+
+            glyph_col < GLYPH_WIDTH
+                && line[glyph][glyph_row as usize] & (1 << (GLYPH_WIDTH - 1 - glyph_col)) != 0
+        })
+    }
+}
+
+/// Shape of the rings of [`ConcentricGenerator`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RingShape {
+    /// Rectangles.
+    Rectangles,
+    /// Squares standing on a corner.
+    Diamonds,
+    /// Circles, as round as cells allow.
+    Circles,
+}
+
+/// Every other ring around the center of the area, the rings with bricks are picked at random.
+pub struct ConcentricGenerator {
+    /// Shape of the rings.
+    pub shape: RingShape,
+}
+
+impl LevelGenerator for ConcentricGenerator {
+    fn generate(
+        &self,
+        params: &LevelParams,
+        cols: u32,
+        rows: u32,
+        rng: &mut dyn RngCore,
+    ) -> LevelDesign {
+        let parity = rng.gen_range(0..2);
+        let center_x = (cols as f32 - 1.) / 2.;
+        let center_y = (rows as f32 - 1.) / 2.;
+
+        design_from_cells(params, cols, rows, |col, row| {
+            let dx = (col as f32 - center_x).abs();
+            let dy = (row as f32 - center_y).abs();
+            let distance = match self.shape {
+                RingShape::Rectangles => dx.max(dy),
+                RingShape::Diamonds => dx + dy,
+                RingShape::Circles => (dx * dx + dy * dy).sqrt(),
+            };
+            (distance + 0.5).floor() as u32 % 2 == parity
+        })
+    }
+}
+
+/// Classic full rows from the top, more rows with a higher brick spawn probability.
+pub struct RowsGenerator;
+
+impl LevelGenerator for RowsGenerator {
+    fn generate(
+        &self,
+        params: &LevelParams,
+        cols: u32,
+        rows: u32,
+        _rng: &mut dyn RngCore,
+    ) -> LevelDesign {
+        let full_rows = ((rows as f64 * params.brick_spawn_probability).ceil() as u32).max(1);
+        design_from_cells(params, cols, rows, |_, row| {
+            row >= rows.saturating_sub(full_rows)
+        })
+    }
+}
+
+/// Empty cells of `design` connected to the outside of the area by empty cells, indexed by `row * cols + col`,
+/// and the first empty cell which isn’t, if any.
+fn reached_cells(design: &LevelDesign) -> (Vec<bool>, Option<(u32, u32)>) {
+    let (cols, rows) = (design.cols, design.rows);
+    let index = |col: u32, row: u32| (row * cols + col) as usize;
+
+    let mut has_brick = vec![false; (cols * rows) as usize];
+    for brick in design.bricks.iter() {
+        has_brick[index(brick.col, brick.row)] = true;
+    }
+
+    // Flood the empty cells from the border of the area
+    let mut reached = vec![false; has_brick.len()];
+    let mut stack: Vec<(u32, u32)> = (0..cols)
+        .flat_map(|col| (0..rows).map(move |row| (col, row)))
+        .filter(|&(col, row)| col == 0 || row == 0 || col == cols - 1 || row == rows - 1)
+        .filter(|&(col, row)| !has_brick[index(col, row)])
+        .collect();
+    while let Some((col, row)) = stack.pop() {
+        if reached[index(col, row)] {
+            continue;
+        }
+        reached[index(col, row)] = true;
+
+        let neighbors = [
+            (col.wrapping_sub(1), row),
+            (col + 1, row),
+            (col, row.wrapping_sub(1)),
+            (col, row + 1),
+        ];
+        for (col, row) in neighbors {
+            if col < cols && row < rows && !has_brick[index(col, row)] {
+                stack.push((col, row));
+            }
+        }
+    }
+
+    let pocket = (0..cols)
+        .flat_map(|col| (0..rows).map(move |row| (col, row)))
+        .find(|&(col, row)| !has_brick[index(col, row)] && !reached[index(col, row)]);
+
+    (reached, pocket)
+}
+
+/// Remove bricks until every empty cell is connected to the outside of the area by empty cells,
+/// so the ball can reach it without breaking bricks.
+pub fn open_pockets(design: &mut LevelDesign) {
+    let cols = design.cols;
+    let index = |col: u32, row: u32| (row * cols + col) as usize;
+
+    loop {
+        let (reached, pocket) = reached_cells(design);
+        let (col, pocket_row) = match pocket {
+            Some(pocket) => pocket,
+            None => return,
+        };
+
+        // Dig down to the nearest reached cell, or to the bottom of the area
+        let mut row = pocket_row;
+        while row > 0 && !reached[index(col, row - 1)] {
+            row -= 1;
+            design.erase(col, row);
+        }
+    }
+}
+
+/// Generate the level `params` with the seed `seed` in an area of `cols` by `rows`,
+/// following the constraints of `cfg`.
+pub fn generate_level(
+    params: &LevelParams,
+    cols: u32,
+    rows: u32,
+    seed: u64,
+    cfg: &GameConfig,
+) -> LevelDesign {
+    let generator = params.generator.generator();
+    let allowed_cells = (0..cols)
+        .flat_map(|col| (0..rows).map(move |row| (col, row)))
+        .filter(|&(col, row)| params.movement.allows(col, row))
+        .count();
+    let min_bricks = (cfg.level_min_bricks as usize).min(allowed_cells);
+
+    let mut best: Option<LevelDesign> = None;
+    for attempt in 0..MAX_ATTEMPTS {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed.wrapping_add(attempt));
+        let mut design = generator.generate(params, cols, rows, &mut rng);
+        if cfg.level_reachable {
+            open_pockets(&mut design);
+        }
+
+        if design.bricks.len() >= min_bricks {
+            return design;
+        }
+        if best
+            .as_ref()
+            .map_or(true, |best| design.bricks.len() > best.bricks.len())
+        {
+            best = Some(design);
+        }
+    }
+
+    // Fill in random empty cells, even out of the layout, a brick closing a pocket is skipped.
+    // An empty cell always remains whose brick closes no pocket: the last one of a path from the border.
+    let mut design = best.unwrap_or_else(|| design_from_cells(params, cols, rows, |_, _| false));
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut empty_cells: Vec<(u32, u32)> = (0..cols)
+        .flat_map(|col| (0..rows).map(move |row| (col, row)))
+        .filter(|&(col, row)| design.brick(col, row).is_none() && params.movement.allows(col, row))
+        .collect();
+    empty_cells.shuffle(&mut rng);
+    while design.bricks.len() < min_bricks {
+        let filled = empty_cells.iter().position(|&(col, row)| {
+            design.paint(design_brick(params, col, row, rows));
+            let kept = !cfg.level_reachable || reached_cells(&design).1.is_none();
+            if !kept {
+                design.erase(col, row);
+            }
+            kept
+        });
+        match filled {
+            Some(filled) => {
+                empty_cells.remove(filled);
+            }
+            None => break,
+        }
+    }
+
+    design
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::breakout_game::level::{level_params, BrickLayout};
+
+    /// Every generator, some of the text generator’s words don’t fit in the default area.
+    fn generators() -> Vec<GeneratorKind> {
+        vec![
+            GeneratorKind::Noise,
+            GeneratorKind::MirrorNoise,
+            GeneratorKind::Caves {
+                fill: 0.45,
+                steps: 4,
+            },
+            GeneratorKind::Text {
+                words: vec!["HELLO".to_string(), "HI".to_string()],
+            },
+            GeneratorKind::Concentric {
+                shape: RingShape::Rectangles,
+            },
+            GeneratorKind::Concentric {
+                shape: RingShape::Diamonds,
+            },
+            GeneratorKind::Concentric {
+                shape: RingShape::Circles,
+            },
+            GeneratorKind::Rows,
+        ]
+    }
+
+    /// Parameters of the first level with every generator and layout.
+    fn all_params(cfg: &GameConfig) -> Vec<LevelParams> {
+        let layouts = [
+            BrickLayout::Full,
+            BrickLayout::Checkerboard,
+            BrickLayout::Pyramid,
+            BrickLayout::Columns,
+        ];
+        generators()
+            .into_iter()
+            .flat_map(|generator| {
+                layouts.map(|layout| LevelParams {
+                    layout,
+                    generator: generator.clone(),
+                    ..level_params(1, cfg)
+                })
+            })
+            .collect()
+    }
+
+    /// Levels of every generator and layout for a few seeds, with more bricks than some generators give.
+    fn generated_levels(cfg: &GameConfig) -> Vec<(LevelParams, LevelDesign)> {
+        let mut levels = Vec::new();
+        for params in all_params(cfg) {
+            for seed in 0..16 {
+                let design =
+                    generate_level(&params, cfg.brick_area_cols, cfg.brick_area_rows, seed, cfg);
+                levels.push((params.clone(), design));
+            }
+        }
+        levels
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_level() {
+        let cfg = GameConfig::default();
+        for params in all_params(&cfg) {
+            let first = generate_level(&params, cfg.brick_area_cols, cfg.brick_area_rows, 7, &cfg);
+            let second = generate_level(&params, cfg.brick_area_cols, cfg.brick_area_rows, 7, &cfg);
+            assert_eq!(first, second, "{:?}", params);
+        }
+    }
+
+    #[test]
+    fn levels_have_the_minimum_number_of_bricks() {
+        for level_reachable in [false, true] {
+            let cfg = GameConfig {
+                level_min_bricks: 40,
+                level_reachable,
+                ..Default::default()
+            };
+            for (params, design) in generated_levels(&cfg) {
+                assert!(design.bricks.len() >= 40, "{:?}", params);
+            }
+        }
+    }
+
+    #[test]
+    fn every_empty_cell_is_reachable() {
+        let cfg = GameConfig {
+            level_min_bricks: 40,
+            level_reachable: true,
+            ..Default::default()
+        };
+        for (params, design) in generated_levels(&cfg) {
+            assert_eq!(reached_cells(&design).1, None, "{:?}", params);
+        }
+    }
+
+    #[test]
+    fn text_generator_picks_the_words_which_fit() {
+        let cfg = GameConfig::default();
+        let params = LevelParams {
+            layout: BrickLayout::Full,
+            generator: GeneratorKind::Text {
+                words: vec!["HELLO".to_string(), "HI".to_string()],
+            },
+            ..level_params(1, &cfg)
+        };
+
+        for seed in 0..8 {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let design = params.generator.generator().generate(
+                &params,
+                cfg.brick_area_cols,
+                cfg.brick_area_rows,
+                &mut rng,
+            );
+            // The bricks of `H` and `I`
+            assert_eq!(design.bricks.len(), 11 + 9);
+        }
+    }
+}
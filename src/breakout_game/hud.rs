@@ -0,0 +1,351 @@
+//! HUD related stuff.
+//!
+//! The HUD is made of two panels anchored to the top corners of the playfield:
+//! score, high score and elapsed time on the left, lives, level and active effects on the right.
+//! With several players, the scores of all players are shown in order.
+//!
+//! A banner is displayed in the middle of the playfield when a new level starts and on game over.
+//!
+//!  - (Re)spawn the HUD when the configuration, the theme or the language changes at [`GameStage::Ui`] stage.
+//!  - Show the banner when the level changes or on game over at [`GameStage::Ui`] stage.
+//!  - Update the HUD from the resources at [`GameStage::Ui`] stage.
+
+use super::{
+    components::{HudRoot, HudText, HudValue, LifeIcon, Player, Score},
+    events::GameOver,
+    resources::{
+        ActiveEffects, ActiveTheme, ElapsedTime, GameAssets, GameConfig, HighScore, HudState,
+        Level, Lives, Localization,
+    },
+    GameStage,
+};
+use bevy::prelude::*;
+
+/// HUD logic as a Bevy’s plugin.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HudState::default())
+            .add_system_to_stage(GameStage::Ui, spawn_hud_system)
+            .add_system_to_stage(GameStage::Ui, level_banner_system)
+            .add_system_to_stage(
+                GameStage::Ui,
+                game_over_banner_system.after(level_banner_system),
+            )
+            .add_system_to_stage(
+                GameStage::Ui,
+                update_hud_text_system.after(game_over_banner_system),
+            )
+            .add_system_to_stage(GameStage::Ui, update_life_icon_system);
+    }
+}
+
+/// Build a text with a label section and a value section.
+fn hud_text(
+    label: &str,
+    value: HudValue,
+    font_size: f32,
+    game_assets: &GameAssets,
+    theme: &ActiveTheme,
+) -> (TextBundle, HudText) {
+    (
+        TextBundle::from_sections([
+            TextSection::new(
+                label,
+                TextStyle {
+                    font: game_assets.score_font.clone(),
+                    font_size,
+                    color: theme.0.hud_label,
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font: game_assets.score_font.clone(),
+                font_size,
+                color: theme.0.hud_value,
+            }),
+        ]),
+        HudText { value, section: 1 },
+    )
+}
+
+/// Build a panel anchored to the top left or top right corner.
+fn hud_panel(right: bool, cfg: &GameConfig) -> NodeBundle {
+    let margin = Val::Px(cfg.wall_thickness * 2.);
+
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: if right {
+                UiRect {
+                    right: margin,
+                    top: margin,
+                    ..Default::default()
+                }
+            } else {
+                UiRect {
+                    left: margin,
+                    top: margin,
+                    ..Default::default()
+                }
+            },
+            flex_direction: FlexDirection::Column,
+            align_items: if right {
+                AlignItems::FlexEnd
+            } else {
+                AlignItems::FlexStart
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Show `text` in the middle of the playfield for [`GameConfig::hud_banner_duration`].
+pub fn show_banner(hud_state: &mut HudState, text: String, cfg: &GameConfig) {
+    hud_state.banner = text;
+    hud_state.banner_timer = Timer::from_seconds(cfg.hud_banner_duration, TimerMode::Once);
+}
+
+fn spawn_hud_system(
+    mut commands: Commands,
+    root_query: Query<Entity, With<HudRoot>>,
+    game_assets: Res<GameAssets>,
+    theme: Res<ActiveTheme>,
+    localization: Res<Localization>,
+    cfg: Res<GameConfig>,
+) {
+    if !cfg.is_changed()
+        && !theme.is_changed()
+        && !localization.is_changed()
+        && !game_assets.is_changed()
+    {
+        return;
+    }
+
+    for root in root_query.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+
+    let score_font_size = cfg.score_font_size * cfg.ui_scale;
+    let font_size = cfg.hud_font_size * cfg.ui_scale;
+    // Life icons are small paddles
+    let icon_size = cfg.paddle_size * 0.25 * cfg.ui_scale;
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(cfg.window_width), Val::Px(cfg.window_height)),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(HudRoot)
+        .with_children(|parent| {
+            parent
+                .spawn(hud_panel(false, &cfg))
+                .with_children(|parent| {
+                    parent.spawn(hud_text(
+                        &localization.text("hud-score"),
+                        HudValue::Score,
+                        score_font_size,
+                        &game_assets,
+                        &theme,
+                    ));
+                    parent.spawn(hud_text(
+                        &localization.text("hud-high-score"),
+                        HudValue::HighScore,
+                        font_size,
+                        &game_assets,
+                        &theme,
+                    ));
+                    parent.spawn(hud_text(
+                        &localization.text("hud-time"),
+                        HudValue::Time,
+                        font_size,
+                        &game_assets,
+                        &theme,
+                    ));
+                });
+
+            parent.spawn(hud_panel(true, &cfg)).with_children(|parent| {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            margin: UiRect::bottom(Val::Px(icon_size.y)),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        for index in 0..cfg.hud_max_life_icons {
+                            parent
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Px(icon_size.x), Val::Px(icon_size.y)),
+                                        margin: UiRect::left(Val::Px(icon_size.y)),
+                                        ..Default::default()
+                                    },
+                                    background_color: theme.0.paddle.into(),
+                                    ..Default::default()
+                                })
+                                .insert(LifeIcon(index));
+                        }
+                    });
+                parent.spawn(hud_text(
+                    &localization.text("hud-level"),
+                    HudValue::Level,
+                    font_size,
+                    &game_assets,
+                    &theme,
+                ));
+                parent.spawn(hud_text(
+                    "",
+                    HudValue::Effects,
+                    font_size,
+                    &game_assets,
+                    &theme,
+                ));
+            });
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                        position_type: PositionType::Absolute,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(hud_text(
+                        "",
+                        HudValue::Banner,
+                        score_font_size,
+                        &game_assets,
+                        &theme,
+                    ));
+                });
+        });
+}
+
+fn level_banner_system(
+    mut hud_state: ResMut<HudState>,
+    level: Res<Level>,
+    localization: Res<Localization>,
+    cfg: Res<GameConfig>,
+) {
+    if level.is_changed() {
+        let number = level.number.to_string();
+        let text = localization.format("level-banner", &[("number", &number)]);
+        show_banner(&mut hud_state, text, &cfg);
+    }
+}
+
+/// Run after [`level_banner_system`] to replace the banner of the first level.
+fn game_over_banner_system(
+    mut game_over_events: EventReader<GameOver>,
+    mut hud_state: ResMut<HudState>,
+    localization: Res<Localization>,
+    cfg: Res<GameConfig>,
+) {
+    if let Some(event) = game_over_events.iter().next() {
+        let text = match event.winner {
+            Some(player) => {
+                let number = (player.0 + 1).to_string();
+                localization.format("versus-winner", &[("number", &number)])
+            }
+            None => localization.text("game-over"),
+        };
+        show_banner(&mut hud_state, text, &cfg);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_hud_text_system(
+    time: Res<Time>,
+    mut hud_state: ResMut<HudState>,
+    mut text_query: Query<(&mut Text, &HudText)>,
+    score_query: Query<(&Score, &Player)>,
+    high_score: Res<HighScore>,
+    level: Res<Level>,
+    localization: Res<Localization>,
+    elapsed_time: Res<ElapsedTime>,
+    effects: Res<ActiveEffects>,
+    cfg: Res<GameConfig>,
+) {
+    let mut scores: Vec<(Player, i32)> = score_query
+        .iter()
+        .map(|(score, &player)| (player, score.0))
+        .collect();
+    scores.sort_by_key(|&(player, _)| player.0);
+
+    // Count up (or down) toward the actual score of the first player
+    let score = scores.first().map_or(0, |&(_, score)| score) as f32;
+    let step = (score - hud_state.displayed_score)
+        * (cfg.hud_score_count_up_rate * time.delta_seconds()).min(1.);
+    hud_state.displayed_score += step;
+    if (score - hud_state.displayed_score).abs() < 0.5 {
+        hud_state.displayed_score = score;
+    }
+    hud_state.banner_timer.tick(time.delta());
+
+    for (mut text, hud_text) in text_query.iter_mut() {
+        let value = match hud_text.value {
+            HudValue::Score if scores.len() > 1 => scores
+                .iter()
+                .map(|(_, score)| score.to_string())
+                .collect::<Vec<_>>()
+                .join(" - "),
+            HudValue::Score => format!("{}", hud_state.displayed_score.round() as i32),
+            HudValue::HighScore => {
+                let score = high_score.score.to_string();
+                let level = high_score.level.to_string();
+                localization.format(
+                    "hud-high-score-value",
+                    &[("score", &score), ("level", &level)],
+                )
+            }
+            HudValue::Level if level.name.is_empty() => level.number.to_string(),
+            HudValue::Level => format!("{} - {}", level.number, level.name),
+            HudValue::Time => {
+                let seconds = elapsed_time.0.elapsed().as_secs();
+                format!("{}:{:02}", seconds / 60, seconds % 60)
+            }
+            HudValue::Effects => effects
+                .0
+                .iter()
+                .map(|effect| {
+                    format!(
+                        "{} {:.0}s",
+                        effect.name,
+                        effect.timer.remaining_secs().ceil()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            HudValue::Banner if hud_state.banner_timer.finished() => String::new(),
+            HudValue::Banner => hud_state.banner.clone(),
+        };
+
+        if text.sections[hud_text.section].value != value {
+            text.sections[hud_text.section].value = value;
+        }
+    }
+}
+
+fn update_life_icon_system(mut icon_query: Query<(&mut Style, &LifeIcon)>, lives: Res<Lives>) {
+    for (mut style, icon) in icon_query.iter_mut() {
+        let display = if icon.0 < lives.0 {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
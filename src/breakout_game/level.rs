@@ -0,0 +1,365 @@
+//! Level related stuff.
+//!
+//! Each level is harder than the previous one, following the [`DifficultyCurve`] of [`GameConfig`]:
+//! the ball is faster, the paddle is smaller, there are more bricks with more hit points
+//! and the bricks are laid out and move differently.
+//!
+//!  - Insert a [`Level`] resource.
+//!  - Go to the next level on [`LevelCleared`] at [`GameStage::Events`] stage.
+//!  - Go back to the first level on [`GameOver`] at [`GameStage::Events`] stage.
+
+use super::{
+    events::{GameOver, LevelCleared},
+    generator::{GeneratorKind, RingShape},
+    resources::{GameConfig, Level},
+    GameStage,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+/// Maximum number of columns or rows of a level.
+pub const MAX_AREA_SIZE: u32 = 64;
+
+/// Level logic as a Bevy’s plugin. (see the game rules)
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Level::default())
+            .add_system_to_stage(GameStage::Events, next_level_system);
+    }
+}
+
+/// Which cells of the brick’s spawning area may have a brick.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BrickLayout {
+    /// Every cell.
+    Full,
+    /// Every other cell, like the black squares of a checkerboard.
+    Checkerboard,
+    /// A pyramid, narrower at the top.
+    Pyramid,
+    /// Columns separated by a gap every third column.
+    Columns,
+}
+
+impl BrickLayout {
+    /// Check if the cell at `col` and `row` may have a brick in an area of `cols` by `rows`.
+    pub fn contains(self, col: u32, row: u32, cols: u32, rows: u32) -> bool {
+        match self {
+            BrickLayout::Full => true,
+            BrickLayout::Checkerboard => (col + row) % 2 == 0,
+            BrickLayout::Pyramid => {
+                let margin = row * cols / (2 * rows.max(1));
+                col >= margin && col < cols - margin
+            }
+            BrickLayout::Columns => col % 3 != 2,
+        }
+    }
+}
+
+/// How the bricks of a level move, see [`super::motion`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum BrickMovement {
+    /// Bricks don’t move.
+    Static,
+    /// Bricks go left and right around their cell.
+    Oscillate {
+        /// Maximum distance from the cell in pixels.
+        amplitude: f32,
+        /// Speed in pixels per second.
+        speed: f32,
+    },
+    /// Bricks go slowly toward the paddle.
+    Drift {
+        /// Speed in pixels per second.
+        speed: f32,
+    },
+    /// Groups of 2x2 bricks turn on a small circle starting at their cell, neighbor groups in opposite phase.
+    ///
+    /// The groups are one cell apart, see [`BrickMovement::allows`], and the radius is at most
+    /// a quarter of a brick’s width and half its height, so neighbor groups never overlap.
+    Orbit {
+        /// Radius of the circle in pixels.
+        radius: f32,
+        /// Duration of a turn in seconds.
+        period: f32,
+    },
+    /// All the rows go down one step at a time.
+    Descend {
+        /// Distance of a step in pixels.
+        step: f32,
+        /// Duration between two steps in seconds.
+        interval: f32,
+    },
+}
+
+impl BrickMovement {
+    /// Check if a brick at `col` and `row` can move without overlapping its neighbors.
+    pub fn allows(self, col: u32, row: u32) -> bool {
+        match self {
+            BrickMovement::Orbit { .. } => col % 3 != 2 && row % 3 != 2,
+            _ => true,
+        }
+    }
+}
+
+/// How the game parameters change from one level to the next.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DifficultyCurve {
+    /// Ball’s speed added at each level, in fraction of [`GameConfig::ball_base_speed`].
+    pub ball_speed_step: f32,
+    /// Maximum ball’s speed, in fraction of [`GameConfig::ball_base_speed`].
+    pub ball_speed_max: f32,
+    /// Paddle’s width removed at each level, in fraction of [`GameConfig::paddle_size`].
+    pub paddle_shrink_step: f32,
+    /// Minimum paddle’s width, in fraction of [`GameConfig::paddle_size`].
+    pub paddle_scale_min: f32,
+    /// Probability added to [`GameConfig::brick_spawn_probability`] at each level.
+    pub brick_probability_step: f64,
+    /// Number of levels to gain a hit point on the top row bricks, 0 to always have a single hit point.
+    pub brick_hit_points_every: u32,
+    /// Maximum number of hit points of a brick.
+    pub brick_hit_points_max: u32,
+    /// Layouts of the levels, repeated if there are more levels than layouts.
+    pub layouts: Vec<BrickLayout>,
+    /// Brick’s movements of the levels, repeated if there are more levels than movements.
+    pub movements: Vec<BrickMovement>,
+    /// Generators of the levels, repeated if there are more levels than generators.
+    pub generators: Vec<GeneratorKind>,
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> DifficultyCurve {
+        DifficultyCurve {
+            ball_speed_step: 0.08,
+            ball_speed_max: 1.8,
+            paddle_shrink_step: 0.05,
+            paddle_scale_min: 0.6,
+            brick_probability_step: 0.05,
+            brick_hit_points_every: 3,
+            brick_hit_points_max: 3,
+            layouts: vec![
+                BrickLayout::Full,
+                BrickLayout::Checkerboard,
+                BrickLayout::Pyramid,
+                BrickLayout::Columns,
+            ],
+            movements: vec![
+                BrickMovement::Static,
+                BrickMovement::Static,
+                BrickMovement::Oscillate {
+                    amplitude: 40.,
+                    speed: 30.,
+                },
+                BrickMovement::Orbit {
+                    radius: 8.,
+                    period: 3.,
+                },
+                BrickMovement::Descend {
+                    step: 10.,
+                    interval: 8.,
+                },
+                BrickMovement::Drift { speed: 1.5 },
+            ],
+            generators: vec![
+                GeneratorKind::Noise,
+                GeneratorKind::Rows,
+                GeneratorKind::MirrorNoise,
+                GeneratorKind::Concentric {
+                    shape: RingShape::Rectangles,
+                },
+                GeneratorKind::Caves {
+                    fill: 0.45,
+                    steps: 4,
+                },
+                GeneratorKind::MirrorNoise,
+                GeneratorKind::Concentric {
+                    shape: RingShape::Diamonds,
+                },
+            ],
+        }
+    }
+}
+
+/// A brick of a [`LevelDesign`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct DesignBrick {
+    /// Column, from left to right.
+    pub col: u32,
+    /// Row, from bottom to top.
+    pub row: u32,
+    /// Hits to destroy the brick, the brick is [`super::components::BrickKind::Strong`] above one.
+    pub hit_points: u32,
+    /// Fill color, the theme’s color if none.
+    pub color: Option<Color>,
+}
+
+/// Bricks of a level placed by hand, in a level file or with [`super::editor`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LevelDesign {
+    /// Number of columns of the brick’s spawning area.
+    pub cols: u32,
+    /// Number of rows of the brick’s spawning area.
+    pub rows: u32,
+    /// Bricks, at most one by cell.
+    pub bricks: Vec<DesignBrick>,
+    /// Movement of the bricks, following the [`DifficultyCurve`] if none.
+    #[serde(default)]
+    pub movement: Option<BrickMovement>,
+}
+
+impl LevelDesign {
+    /// Empty level with the brick’s spawning area of `cfg`.
+    pub fn empty(cfg: &GameConfig) -> LevelDesign {
+        LevelDesign {
+            cols: cfg.brick_area_cols,
+            rows: cfg.brick_area_rows,
+            bricks: Vec::new(),
+            movement: None,
+        }
+    }
+
+    /// The brick at `col` and `row`, if there is one.
+    pub fn brick(&self, col: u32, row: u32) -> Option<&DesignBrick> {
+        self.bricks
+            .iter()
+            .find(|brick| brick.col == col && brick.row == row)
+    }
+
+    /// Place `brick` in its cell, replacing the previous one.
+    pub fn paint(&mut self, brick: DesignBrick) {
+        self.erase(brick.col, brick.row);
+        self.bricks.push(brick);
+    }
+
+    /// Remove the brick at `col` and `row`, if there is one.
+    pub fn erase(&mut self, col: u32, row: u32) {
+        self.bricks
+            .retain(|brick| brick.col != col || brick.row != row);
+    }
+
+    /// Change the size of the area, bricks out of it are removed.
+    pub fn resize(&mut self, cols: u32, rows: u32) {
+        self.cols = cols;
+        self.rows = rows;
+        self.bricks
+            .retain(|brick| brick.col < cols && brick.row < rows);
+    }
+
+    /// Read a level file in RON format, invalid levels are [`io::ErrorKind::InvalidData`].
+    pub fn load(path: &Path) -> io::Result<LevelDesign> {
+        let content = fs::read_to_string(path)?;
+        let design: LevelDesign = ron::from_str(&content)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        design.check()?;
+        Ok(design)
+    }
+
+    /// Check the size of the area and that each brick has its own cell in it.
+    fn check(&self) -> io::Result<()> {
+        let invalid = |message: String| -> io::Result<()> {
+            Err(io::Error::new(io::ErrorKind::InvalidData, message))
+        };
+
+        let sizes = 1..=MAX_AREA_SIZE;
+        if !sizes.contains(&self.cols) || !sizes.contains(&self.rows) {
+            return invalid(format!("Invalid area of {} by {}", self.cols, self.rows));
+        }
+        for (index, brick) in self.bricks.iter().enumerate() {
+            let cell = (brick.col, brick.row);
+            if brick.col >= self.cols || brick.row >= self.rows {
+                return invalid(format!("Brick out of the area at {:?}", cell));
+            }
+            if self.bricks[..index]
+                .iter()
+                .any(|other| (other.col, other.row) == cell)
+            {
+                return invalid(format!("Several bricks at {:?}", cell));
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a level file in RON format, creating its directory.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        fs::write(path, content)
+    }
+}
+
+/// Game parameters of a level.
+#[derive(Clone, Debug)]
+pub struct LevelParams {
+    /// Ball’s speed in pixels per second.
+    pub ball_speed: f32,
+    /// Paddle’s width in pixels.
+    pub paddle_width: f32,
+    /// Probability of a brick to spawn in a cell of the layout.
+    pub brick_spawn_probability: f64,
+    /// Hit points of the top row bricks, one less for each row below, at least one.
+    pub brick_hit_points: u32,
+    /// Cells which may have a brick.
+    pub layout: BrickLayout,
+    /// How the bricks move.
+    pub movement: BrickMovement,
+    /// How the bricks are placed.
+    pub generator: GeneratorKind,
+}
+
+/// Game parameters of the level `number`, starting at 1.
+pub fn level_params(number: u32, cfg: &GameConfig) -> LevelParams {
+    let curve = &cfg.difficulty_curve;
+    let steps = number.saturating_sub(1);
+
+    let brick_hit_points = match curve.brick_hit_points_every {
+        0 => 1,
+        every => (1 + steps / every).min(curve.brick_hit_points_max.max(1)),
+    };
+    let layout = match curve.layouts.len() {
+        0 => BrickLayout::Full,
+        len => curve.layouts[steps as usize % len],
+    };
+    let movement = match curve.movements.len() {
+        0 => BrickMovement::Static,
+        len => curve.movements[steps as usize % len],
+    };
+    let generator = match (&cfg.level_generator, curve.generators.len()) {
+        (Some(generator), _) => generator.clone(),
+        (None, 0) => GeneratorKind::Noise,
+        (None, len) => curve.generators[steps as usize % len].clone(),
+    };
+
+    LevelParams {
+        ball_speed: cfg.ball_base_speed
+            * (1. + curve.ball_speed_step * steps as f32).min(curve.ball_speed_max),
+        paddle_width: cfg.paddle_size.x
+            * (1. - curve.paddle_shrink_step * steps as f32).max(curve.paddle_scale_min),
+        brick_spawn_probability: (cfg.brick_spawn_probability
+            + curve.brick_probability_step * steps as f64)
+            .min(1.),
+        brick_hit_points,
+        layout,
+        movement,
+        generator,
+    }
+}
+
+fn next_level_system(
+    mut level_cleared_events: EventReader<LevelCleared>,
+    mut game_over_events: EventReader<GameOver>,
+    mut level: ResMut<Level>,
+) {
+    for _ in level_cleared_events.iter() {
+        level.number += 1;
+    }
+
+    if game_over_events.iter().next().is_some() {
+        *level = Level::default();
+    }
+}
@@ -0,0 +1,208 @@
+//! Localization related stuff.
+//!
+//! All the text displayed to the player comes from a [`Catalog`] of the active language.
+//! Catalogs are written in a subset of the [Fluent](https://projectfluent.org) syntax:
+//!
+//! ```ftl
+//! # Comment
+//! -language-name = English
+//! -font = fonts/arial.ttf
+//!
+//! hud-score = Score:{" "}
+//! settings-volume = Volume: { $value }%
+//! ```
+//!
+//! Terms starting with `-` describe the catalog: `-language-name` is displayed in the settings,
+//! the optional `-font` replaces [`GameConfig::score_font_path`] for scripts it doesn’t cover.
+//! Keys missing in a catalog fall back to English, they are reported in debug builds.
+//!
+//!  - Insert [`Localization`] resource.
+//!  - Report missing keys at [`StartupStage::Startup`] stage in debug builds.
+//!  - Switch the language, the font and the window title at [`CoreStage::PreUpdate`] stage.
+
+use super::resources::{GameAssets, GameConfig, Localization};
+use bevy::{prelude::*, utils::HashMap};
+
+/// Built-in catalogs by language code, the first one is the fallback catalog.
+const BUILT_IN_CATALOGS: [(&str, &str); 2] = [
+    ("en", include_str!("../../assets/locales/en.ftl")),
+    ("fr", include_str!("../../assets/locales/fr.ftl")),
+];
+
+/// Localization logic as a Bevy’s plugin.
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Localization::built_in())
+            .add_startup_system(report_missing_keys_system)
+            .add_system_to_stage(CoreStage::PreUpdate, switch_language_system);
+    }
+}
+
+/// Messages of a language.
+pub struct Catalog {
+    /// Language code, like `en`.
+    pub language: String,
+    /// Name of the language displayed to the player.
+    pub name: String,
+    /// Path to the font used by this language, relative to the assets folder.
+    pub font: Option<String>,
+    /// Messages by key.
+    pub messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Parse a catalog, invalid lines are ignored.
+    pub fn parse(language: &str, source: &str) -> Catalog {
+        let mut catalog = Catalog {
+            language: language.to_string(),
+            name: language.to_string(),
+            font: None,
+            messages: HashMap::default(),
+        };
+
+        for line in source.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                let key = key.trim();
+                let value = value.trim().to_string();
+                match key {
+                    "-language-name" => catalog.name = value,
+                    "-font" => catalog.font = Some(value),
+                    _ => {
+                        catalog.messages.insert(key.to_string(), value);
+                    }
+                }
+            }
+        }
+
+        catalog
+    }
+}
+
+/// Replace the placeables of a message, `{ $name }` by its argument and `{ "text" }` by its text.
+fn resolve(message: &str, args: &[(&str, &str)]) -> String {
+    let mut resolved = String::with_capacity(message.len());
+    let mut rest = message;
+
+    while let Some(start) = rest.find('{') {
+        resolved.push_str(&rest[..start]);
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        let placeable = rest[start + 1..end].trim();
+        if let Some(name) = placeable.strip_prefix('$') {
+            let value = args.iter().find(|(arg, _)| *arg == name);
+            resolved.push_str(value.map_or(placeable, |(_, value)| value));
+        } else {
+            resolved.push_str(placeable.trim_matches('"'));
+        }
+        rest = &rest[end + 1..];
+    }
+
+    resolved.push_str(rest);
+    resolved
+}
+
+impl Localization {
+    /// Parse the built-in catalogs, English is active.
+    pub fn built_in() -> Localization {
+        Localization {
+            catalogs: BUILT_IN_CATALOGS
+                .iter()
+                .map(|(language, source)| Catalog::parse(language, source))
+                .collect(),
+            active: 0,
+        }
+    }
+
+    /// The active catalog.
+    pub fn catalog(&self) -> &Catalog {
+        &self.catalogs[self.active]
+    }
+
+    /// Index of the catalog of `language`, if there is one.
+    pub fn find(&self, language: &str) -> Option<usize> {
+        self.catalogs
+            .iter()
+            .position(|catalog| catalog.language == language)
+    }
+
+    /// Message of `key` in the active language.
+    pub fn text(&self, key: &str) -> String {
+        self.format(key, &[])
+    }
+
+    /// Message of `key` in the active language, with its `{ $name }` placeables replaced by `args`.
+    ///
+    /// Fall back to English, then to the key itself.
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let message = self
+            .catalog()
+            .messages
+            .get(key)
+            .or_else(|| self.catalogs[0].messages.get(key));
+
+        match message {
+            Some(message) => resolve(message, args),
+            None => {
+                if cfg!(debug_assertions) {
+                    warn!("Missing localization key {}", key);
+                }
+                key.to_string()
+            }
+        }
+    }
+}
+
+fn report_missing_keys_system(localization: Res<Localization>) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    let fallback = &localization.catalogs[0];
+    for catalog in localization.catalogs.iter().skip(1) {
+        for key in fallback.messages.keys() {
+            if !catalog.messages.contains_key(key) {
+                warn!("Missing localization key {} in {}", key, catalog.language);
+            }
+        }
+    }
+}
+
+fn switch_language_system(
+    mut localization: ResMut<Localization>,
+    mut game_assets: ResMut<GameAssets>,
+    mut windows: ResMut<Windows>,
+    asset_server: Res<AssetServer>,
+    cfg: Res<GameConfig>,
+) {
+    if !cfg.is_changed() {
+        return;
+    }
+
+    let active = localization.find(&cfg.language).unwrap_or(0);
+    // Only trigger change detection when the language actually changes
+    if active == localization.active && !localization.is_added() {
+        return;
+    }
+    localization.active = active;
+
+    let font_path = localization
+        .catalog()
+        .font
+        .as_ref()
+        .unwrap_or(&cfg.score_font_path);
+    game_assets.score_font = asset_server.load(font_path);
+
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_title(localization.text("window-title"));
+    }
+}
@@ -0,0 +1,133 @@
+//! Brick motion related stuff.
+//!
+//! Bricks move following the [`BrickMovement`] of the level, their [`Velocity`] is applied by [`super::common`].
+//! Like the paddle, bricks are moved before [`GameStage::Ball`] to bounce the ball with an up to date velocity.
+//!
+//!  - Update the velocity of moving bricks at [`GameStage::Input`] stage.
+//!  - Handle collision of moving bricks with walls at [`GameStage::Paddle`] stage.
+//!  - Send [`GameOver`] once when a brick reaches the line of any paddle at [`GameStage::Paddle`] stage.
+
+use super::{
+    components::{BoundingBox, BrickMotion, Paddle, Player, Velocity, Wall},
+    events::GameOver,
+    level::BrickMovement,
+    paddle::paddle_side,
+    resources::GameConfig,
+    GameStage,
+};
+use bevy::{
+    prelude::*,
+    sprite::collide_aabb::{collide, Collision},
+};
+use std::f32::consts::TAU;
+
+/// Brick motion logic as a Bevy’s plugin. (see the game rules)
+pub struct MotionPlugin;
+
+impl Plugin for MotionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(GameStage::Input, brick_motion_system)
+            .add_system_to_stage(GameStage::Paddle, brick_wall_collision_system)
+            .add_system_to_stage(GameStage::Paddle, paddle_line_system);
+    }
+}
+
+fn brick_motion_system(
+    mut query: Query<(&mut Transform, &mut Velocity, &mut BrickMotion)>,
+    cfg: Res<GameConfig>,
+) {
+    // Same time step as the movement system
+    let dt = cfg.time_step();
+
+    for (mut tf, mut velocity, mut motion) in query.iter_mut() {
+        let previous_elapsed = motion.elapsed;
+        motion.elapsed += dt;
+
+        velocity.0 = match motion.movement {
+            BrickMovement::Static => Vec2::ZERO,
+            BrickMovement::Oscillate { amplitude, speed } => {
+                let offset = tf.translation.x - motion.origin.x;
+                if offset * motion.direction >= amplitude {
+                    motion.direction = -motion.direction;
+                }
+                Vec2::new(motion.direction * speed, 0.)
+            }
+            BrickMovement::Drift { speed } => Vec2::new(0., -speed),
+            BrickMovement::Orbit { radius, period } => {
+                // Neighbor groups in opposite phase are up to twice the diameter closer
+                let radius = radius.min(cfg.brick_size.x / 4.).min(cfg.brick_size.y / 2.);
+                let angular_speed = TAU / period;
+                let angle = motion.phase + angular_speed * motion.elapsed;
+                // Derivative of a position on the circle
+                Vec2::new(-angle.sin(), angle.cos()) * radius * angular_speed
+            }
+            BrickMovement::Descend { step, interval } => {
+                // Teleported rather than moved, one row at a time
+                if (motion.elapsed / interval).floor() > (previous_elapsed / interval).floor() {
+                    tf.translation.y -= step;
+                }
+                Vec2::ZERO
+            }
+        };
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn brick_wall_collision_system(
+    mut brick_query: Query<
+        (
+            &mut Transform,
+            &BoundingBox,
+            &mut Velocity,
+            &mut BrickMotion,
+        ),
+        Without<Wall>,
+    >,
+    wall_query: Query<(&Transform, &BoundingBox), With<Wall>>,
+) {
+    for (mut brick_tf, brick_bb, mut brick_v, mut motion) in brick_query.iter_mut() {
+        for (wall_tf, wall_bb) in wall_query.iter() {
+            let collision = collide(
+                wall_tf.translation,
+                wall_bb.0,
+                brick_tf.translation,
+                brick_bb.0,
+            );
+
+            if let Some(Collision::Right) = collision {
+                motion.direction = -1.;
+                brick_v.0.x = -brick_v.0.x.abs();
+                brick_tf.translation.x =
+                    wall_tf.translation.x - wall_bb.0.x / 2. - brick_bb.0.x / 2.;
+            } else if let Some(Collision::Left) = collision {
+                motion.direction = 1.;
+                brick_v.0.x = brick_v.0.x.abs();
+                brick_tf.translation.x =
+                    wall_tf.translation.x + wall_bb.0.x / 2. + brick_bb.0.x / 2.;
+            }
+        }
+    }
+}
+
+fn paddle_line_system(
+    brick_query: Query<(&Transform, &BoundingBox), With<BrickMotion>>,
+    paddle_query: Query<(&Transform, &BoundingBox, &Player), With<Paddle>>,
+    mut game_over_events: EventWriter<GameOver>,
+    mut was_reached: Local<bool>,
+    cfg: Res<GameConfig>,
+) {
+    let reached = paddle_query.iter().any(|(paddle_tf, paddle_bb, &player)| {
+        // Flipped for the top paddle, to compare with the face of the paddle toward the bricks
+        let side = paddle_side(player, &cfg);
+        let paddle_line = side * paddle_tf.translation.y + paddle_bb.0.y / 2.;
+        brick_query.iter().any(|(brick_tf, brick_bb)| {
+            side * brick_tf.translation.y - brick_bb.0.y / 2. <= paddle_line
+        })
+    });
+
+    // Sent once, the bricks stay past the line until they are respawned
+    if reached && !*was_reached {
+        game_over_events.send(GameOver { winner: None });
+    }
+    *was_reached = reached;
+}
@@ -0,0 +1,588 @@
+//! Networked multiplayer in lockstep over UDP, between two peers.
+//!
+//! Both peers run the whole simulation, which is deterministic with the same [`GameConfig`]:
+//! seeded [`GameRng`] and fixed time step.
+//! The first player chooses the seed and sends it in every packet, the first tick waits for it.
+//! Only the [`PlayerInput`] of each tick are exchanged, a tick is simulated once the inputs
+//! of both players are known, the gameplay stages are [`Stalled`] meanwhile.
+//! Local inputs are delayed by [`NetConfig::input_delay`] ticks to hide the latency,
+//! and sent again in every packet until the peer acknowledges them, so lost packets don’t matter.
+//!
+//! Each peer hashes the gameplay state after each tick (bricks, balls, paddles, scores, lives, level and RNG)
+//! and sends its recent hashes, a [`Desync`] is sent when the hashes of a tick differ.
+//!
+//!  - Bind the [`NetSession`] of [`GameConfig::net`], if any, and use a fixed time step at startup.
+//!  - Exchange the inputs and stall the simulation until the next tick’s inputs are known
+//!    at [`CoreStage::PreUpdate`] stage, the [`GameRng`] is seeded before the first tick.
+//!  - Hash the simulated tick and send the local inputs at [`CoreStage::Last`] stage.
+//!
+//! [`NetConfig::input_delay`]: super::resources::NetConfig::input_delay
+
+use super::{
+    components::{Ball, Brick, BrickCell, BrickHitPoints, Paddle, Player, Score, Velocity},
+    controls::{keyboard_input, keyboard_input_system},
+    events::Desync,
+    resources::{
+        GameConfig, GameRng, Level, LinkConditions, Lives, Paused, PlayerInput, PlayerInputs,
+        Stalled,
+    },
+    simulation_clock_system,
+};
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io::{self, ErrorKind},
+    mem,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant},
+};
+
+/// Number of recent hashes sent in each packet.
+const SENT_HASHES: usize = 8;
+
+/// Number of ticks the hashes are kept to be compared with the peer’s.
+const HASH_HISTORY: u32 = 600;
+
+/// Initial value of the FNV-1a hash.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Multiplier of the FNV-1a hash.
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Maximum size of a UDP packet.
+const MAX_PACKET_SIZE: usize = 65507;
+
+/// Network logic as a Bevy’s plugin, it does nothing without a [`NetSession`].
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(init_net_system)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                lockstep_system
+                    .after(keyboard_input_system)
+                    .before(simulation_clock_system),
+            )
+            .add_system_to_stage(CoreStage::Last, hash_state_system);
+    }
+}
+
+/// Content of a packet.
+#[derive(Serialize, Deserialize)]
+struct Packet {
+    /// Tick of the first input.
+    first_tick: u32,
+    /// Inputs of the sender’s player from `first_tick`, not acknowledged yet.
+    inputs: Vec<PlayerInput>,
+    /// The sender has received all the receiver’s inputs before this tick.
+    ack: u32,
+    /// Recent hashes of the sender’s state, by tick.
+    hashes: Vec<(u32, u64)>,
+    /// Seed of the game, if the sender knows it.
+    seed: Option<u64>,
+}
+
+/// UDP socket to the peer, with simulated conditions.
+struct Link {
+    /// Non-blocking socket.
+    socket: UdpSocket,
+    /// Address of the peer, nothing is sent before it is known.
+    peer: Option<SocketAddr>,
+    /// Simulated loss and latency.
+    conditions: LinkConditions,
+    /// Packets delayed by the simulated latency, with their sending time.
+    delayed: Vec<(Instant, Vec<u8>)>,
+    /// Draws the simulated losses and jitter, independent of the gameplay.
+    rng: StdRng,
+}
+
+impl Link {
+    /// Send `bytes` to the peer, unless it is lost, after the simulated latency.
+    fn send(&mut self, bytes: Vec<u8>) {
+        if self.conditions.loss > 0. && self.rng.gen_bool(self.conditions.loss.min(1.)) {
+            return;
+        }
+
+        let jitter = self.conditions.jitter.mul_f64(self.rng.gen());
+        self.delayed
+            .push((Instant::now() + self.conditions.latency + jitter, bytes));
+        self.flush();
+    }
+
+    /// Send the delayed packets whose time has come.
+    fn flush(&mut self) {
+        let peer = match self.peer {
+            Some(peer) => peer,
+            None => return,
+        };
+
+        let now = Instant::now();
+        let socket = &self.socket;
+        self.delayed.retain(|(time, bytes)| {
+            if *time > now {
+                return true;
+            }
+            if let Err(err) = socket.send_to(bytes, peer) {
+                if err.kind() != ErrorKind::WouldBlock {
+                    warn!("Cannot send to {}: {}", peer, err);
+                }
+            }
+            false
+        });
+    }
+
+    /// All the packets received from the peer since the last call.
+    fn receive(&mut self) -> Vec<Vec<u8>> {
+        let mut packets = Vec::new();
+        let mut buffer = vec![0; MAX_PACKET_SIZE];
+
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((len, from)) if Some(from) == self.peer => packets.push(buffer[..len].to_vec()),
+                Ok(_) => (),
+                // The peer isn’t listening yet
+                Err(err) if err.kind() == ErrorKind::ConnectionReset => (),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    warn!("Cannot receive: {}", err);
+                    break;
+                }
+            }
+        }
+
+        packets
+    }
+}
+
+/// Lockstep session with a remote peer.
+#[derive(Resource)]
+pub struct NetSession {
+    /// Socket to the peer.
+    link: Link,
+    /// The local player, the peer plays the other one.
+    local_player: Player,
+    /// Seed of the game, chosen by the first player, unknown to the second one until received.
+    seed: Option<u64>,
+    /// See [`NetConfig::input_delay`](super::resources::NetConfig::input_delay).
+    input_delay: u32,
+    /// Next tick to simulate.
+    tick: u32,
+    /// Known inputs of both players by tick.
+    inputs: BTreeMap<u32, [Option<PlayerInput>; 2]>,
+    /// Next tick to record a local input for.
+    next_local_tick: u32,
+    /// The peer has received all the local inputs before this tick.
+    peer_ack: u32,
+    /// All the remote inputs before this tick have been received.
+    remote_ack: u32,
+    /// A launch key press not recorded yet, presses are only seen on one frame.
+    pending_launch: bool,
+    /// Tick simulated during this frame, hashed at the end of the frame.
+    simulated: Option<u32>,
+    /// Hashes of the local state by tick.
+    local_hashes: BTreeMap<u32, u64>,
+    /// Hashes of the remote state by tick, not compared yet.
+    remote_hashes: BTreeMap<u32, u64>,
+    /// The first desynchronization, if any.
+    desync: Option<Desync>,
+}
+
+impl NetSession {
+    /// Bind a session for `local_player` to `local_addr`, see [`NetSession::connect`].
+    pub fn bind(
+        local_addr: impl ToSocketAddrs,
+        local_player: Player,
+        input_delay: u32,
+        conditions: LinkConditions,
+    ) -> io::Result<NetSession> {
+        let socket = UdpSocket::bind(local_addr)?;
+        socket.set_nonblocking(true)?;
+        let mut rng = StdRng::from_entropy();
+        let seed = (local_player.0 == 0).then(|| rng.gen());
+
+        Ok(NetSession {
+            link: Link {
+                socket,
+                peer: None,
+                conditions,
+                delayed: Vec::new(),
+                rng,
+            },
+            local_player,
+            seed,
+            input_delay,
+            tick: 0,
+            inputs: BTreeMap::new(),
+            next_local_tick: 0,
+            peer_ack: 0,
+            remote_ack: 0,
+            pending_launch: false,
+            simulated: None,
+            local_hashes: BTreeMap::new(),
+            remote_hashes: BTreeMap::new(),
+            desync: None,
+        })
+    }
+
+    /// Exchange with the peer at `peer`, only its packets are received.
+    pub fn connect(&mut self, peer: SocketAddr) {
+        self.link.peer = Some(peer);
+    }
+
+    /// Address of the local socket.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.link.socket.local_addr()
+    }
+
+    /// Play with `seed` rather than a random one, only the first player chooses it.
+    pub fn set_seed(&mut self, seed: u64) {
+        if self.local_index() == 0 {
+            self.seed = Some(seed);
+        }
+    }
+
+    /// Seed of the game, if it is known yet.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Next tick to simulate, the number of simulated ticks.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Hash of the local state after `tick`, if it is recent enough.
+    pub fn hash(&self, tick: u32) -> Option<u64> {
+        self.local_hashes.get(&tick).copied()
+    }
+
+    /// The first desynchronization with the peer, if any.
+    pub fn desync(&self) -> Option<&Desync> {
+        self.desync.as_ref()
+    }
+
+    /// Index of the remote player in the inputs.
+    fn remote_index(&self) -> usize {
+        1 - self.local_index()
+    }
+
+    /// Index of the local player in the inputs.
+    fn local_index(&self) -> usize {
+        self.local_player.0.min(1)
+    }
+
+    /// Process the packets received from the peer.
+    fn receive(&mut self) {
+        for bytes in self.link.receive() {
+            let packet: Packet = match bincode::deserialize(&bytes) {
+                Ok(packet) => packet,
+                Err(err) => {
+                    warn!("Invalid packet: {}", err);
+                    continue;
+                }
+            };
+
+            if self.seed.is_none() {
+                self.seed = packet.seed;
+            }
+
+            let remote = self.remote_index();
+            for (offset, &input) in packet.inputs.iter().enumerate() {
+                let tick = packet.first_tick + offset as u32;
+                // Older inputs have been simulated already
+                if tick >= self.tick {
+                    self.inputs.entry(tick).or_default()[remote] = Some(input);
+                }
+            }
+            self.remote_ack = self.remote_ack.max(self.tick);
+            while self
+                .inputs
+                .get(&self.remote_ack)
+                .map_or(false, |inputs| inputs[remote].is_some())
+            {
+                self.remote_ack += 1;
+            }
+
+            self.peer_ack = self.peer_ack.max(packet.ack);
+            for (tick, hash) in packet.hashes {
+                if tick + HASH_HISTORY >= self.tick {
+                    self.remote_hashes.insert(tick, hash);
+                }
+            }
+        }
+    }
+
+    /// Record the local `input` for the ticks up to the input delay.
+    fn record_local_input(&mut self, input: PlayerInput) {
+        self.pending_launch |= input.launch;
+
+        let local = self.local_index();
+        while self.next_local_tick <= self.tick + self.input_delay {
+            let input = PlayerInput {
+                launch: mem::take(&mut self.pending_launch),
+                ..input
+            };
+            self.inputs.entry(self.next_local_tick).or_default()[local] = Some(input);
+            self.next_local_tick += 1;
+        }
+    }
+
+    /// Inputs of all the players for the next tick, which is then simulated, if they are known.
+    fn next_inputs(&mut self) -> Option<Vec<PlayerInput>> {
+        let inputs = match (self.seed, self.inputs.get(&self.tick)) {
+            (Some(_), Some([Some(first), Some(second)])) => vec![*first, *second],
+            _ => return None,
+        };
+
+        self.simulated = Some(self.tick);
+        self.tick += 1;
+        Some(inputs)
+    }
+
+    /// Compare the hashes of the ticks known by both peers, the first difference is returned once.
+    fn check_hashes(&mut self) -> Option<Desync> {
+        let mut desync = None;
+
+        let local_hashes = &self.local_hashes;
+        self.remote_hashes.retain(|&tick, &mut remote_hash| {
+            let local_hash = match local_hashes.get(&tick) {
+                Some(&local_hash) => local_hash,
+                None => return true,
+            };
+            if local_hash != remote_hash && desync.is_none() {
+                desync = Some(Desync {
+                    tick,
+                    local_hash,
+                    remote_hash,
+                });
+            }
+            false
+        });
+
+        match (&self.desync, desync) {
+            (None, Some(desync)) => {
+                self.desync = Some(desync.clone());
+                Some(desync)
+            }
+            _ => None,
+        }
+    }
+
+    /// Send the local inputs not acknowledged yet and the recent hashes, forget the old ones.
+    fn send(&mut self) {
+        let local = self.local_index();
+        let inputs = (self.peer_ack..self.next_local_tick)
+            .map(|tick| {
+                self.inputs
+                    .get(&tick)
+                    .and_then(|inputs| inputs[local])
+                    .unwrap_or_default()
+            })
+            .collect();
+        let packet = Packet {
+            first_tick: self.peer_ack,
+            inputs,
+            ack: self.remote_ack,
+            hashes: self
+                .local_hashes
+                .iter()
+                .rev()
+                .take(SENT_HASHES)
+                .map(|(&tick, &hash)| (tick, hash))
+                .collect(),
+            seed: self.seed,
+        };
+
+        match bincode::serialize(&packet) {
+            Ok(bytes) => self.link.send(bytes),
+            Err(err) => warn!("Cannot serialize a packet: {}", err),
+        }
+        self.link.flush();
+
+        let oldest_input = self.tick.min(self.peer_ack);
+        self.inputs.retain(|&tick, _| tick >= oldest_input);
+        let oldest_hash = self.tick.saturating_sub(HASH_HISTORY);
+        self.local_hashes.retain(|&tick, _| tick >= oldest_hash);
+    }
+}
+
+/// FNV-1a hash of `bytes`, stable across platforms and Rust releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+fn init_net_system(
+    mut commands: Commands,
+    session: Option<ResMut<NetSession>>,
+    cfg: Res<GameConfig>,
+) {
+    // The same time step on both peers
+    let fixed_time_step =
+        TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(cfg.time_step()));
+
+    if let Some(mut session) = session {
+        if let Some(seed) = cfg.seed {
+            session.set_seed(seed);
+        }
+        commands.insert_resource(fixed_time_step);
+        return;
+    }
+
+    let net = match &cfg.net {
+        Some(net) => net,
+        None => return,
+    };
+    let peer = match net
+        .peer_addr
+        .to_socket_addrs()
+        .map(|mut addrs| addrs.next())
+    {
+        Ok(Some(peer)) => peer,
+        _ => {
+            error!("Invalid peer address {}", net.peer_addr);
+            return;
+        }
+    };
+
+    match NetSession::bind(
+        net.local_addr.as_str(),
+        net.local_player,
+        net.input_delay,
+        net.link,
+    ) {
+        Ok(mut session) => {
+            if let Some(seed) = cfg.seed {
+                session.set_seed(seed);
+            }
+            session.connect(peer);
+            commands.insert_resource(session);
+            commands.insert_resource(fixed_time_step);
+        }
+        Err(err) => error!("Cannot bind {}: {}", net.local_addr, err),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn lockstep_system(
+    session: Option<ResMut<NetSession>>,
+    keys: Res<Input<KeyCode>>,
+    mut inputs: ResMut<PlayerInputs>,
+    mut stalled: ResMut<Stalled>,
+    mut rng: ResMut<GameRng>,
+    paused: Res<Paused>,
+    mut desync_events: EventWriter<Desync>,
+    cfg: Res<GameConfig>,
+) {
+    let mut session = match session {
+        Some(session) => session,
+        None => return,
+    };
+
+    session.receive();
+    if let Some(desync) = session.check_hashes() {
+        error!("Desynchronized from the peer at tick {}", desync.tick);
+        desync_events.send(desync);
+    }
+
+    // The local player plays with the keys of the first player
+    session.record_local_input(keyboard_input(&keys, Player(0), &cfg));
+
+    // A tick must not be skipped while paused
+    match (paused.0, session.next_inputs()) {
+        (false, Some(next_inputs)) => {
+            // The first tick plays the first player’s seed
+            if let (1, Some(seed)) = (session.tick(), session.seed) {
+                rng.0 = ChaCha8Rng::seed_from_u64(seed);
+            }
+            inputs.0 = next_inputs;
+            stalled.0 = false;
+        }
+        _ => stalled.0 = true,
+    }
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn hash_state_system(
+    session: Option<ResMut<NetSession>>,
+    brick_query: Query<(&BrickCell, &Transform, &BrickHitPoints), With<Brick>>,
+    ball_query: Query<(&Transform, &Velocity), With<Ball>>,
+    paddle_query: Query<(&Transform, &Velocity, &Player), With<Paddle>>,
+    score_query: Query<(&Score, &Player)>,
+    lives: Res<Lives>,
+    level: Res<Level>,
+    rng: Res<GameRng>,
+    mut desync_events: EventWriter<Desync>,
+) {
+    let mut session = match session {
+        Some(session) => session,
+        None => return,
+    };
+
+    if let Some(tick) = session.simulated.take() {
+        // Sorted, the order of the entities may differ between peers
+        let bits = |v: Vec3| v.to_array().map(f32::to_bits);
+        let mut bricks: Vec<_> = brick_query
+            .iter()
+            .map(|(cell, tf, hit_points)| (cell.row, cell.col, bits(tf.translation), hit_points.0))
+            .collect();
+        bricks.sort_unstable();
+        let mut balls: Vec<_> = ball_query
+            .iter()
+            .map(|(tf, velocity)| {
+                (
+                    bits(tf.translation),
+                    velocity.0.to_array().map(f32::to_bits),
+                )
+            })
+            .collect();
+        balls.sort_unstable();
+        let mut paddles: Vec<_> = paddle_query
+            .iter()
+            .map(|(tf, velocity, player)| {
+                (
+                    player.0,
+                    bits(tf.translation),
+                    velocity.0.to_array().map(f32::to_bits),
+                )
+            })
+            .collect();
+        paddles.sort_unstable();
+        let mut scores: Vec<_> = score_query
+            .iter()
+            .map(|(score, player)| (player.0, score.0))
+            .collect();
+        scores.sort_unstable();
+
+        // Hashed in a fixed format, the same on both peers whatever their platform and toolchain
+        let state = (
+            bricks,
+            balls,
+            paddles,
+            scores,
+            lives.0,
+            level.number,
+            &rng.0,
+        );
+        let hash = match bincode::serialize(&state) {
+            Ok(bytes) => fnv1a(&bytes),
+            Err(err) => {
+                warn!("Cannot serialize the state: {}", err);
+                0
+            }
+        };
+        session.local_hashes.insert(tick, hash);
+
+        if let Some(desync) = session.check_hashes() {
+            error!("Desynchronized from the peer at tick {}", desync.tick);
+            desync_events.send(desync);
+        }
+    }
+
+    session.send();
+}
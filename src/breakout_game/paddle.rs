@@ -1,12 +1,16 @@
 //! Paddle related stuff.
 //!
-//!  - Spawn the paddle once at [`StartupStage::PostStartup`] stage.
-//!  - Process input and update [`Velocity`] at [`GameStage::Input`] stage.
-//!  - Handle collision of the paddle with walls at [`GameStage::Paddle`] stage.
+//!  - Spawn a paddle per [`Player`] once at [`StartupStage::PostStartup`] stage.
+//!    In [`GameMode::Versus`], the second player’s paddle is at the top of the window,
+//!    in [`GameMode::Coop`] the paddles are side by side.
+//!  - Process the input of each player and update [`Velocity`] at [`GameStage::Input`] stage.
+//!  - Resize the paddle when the [`Level`] changes at [`GameStage::Paddle`] stage.
+//!  - Handle collision of the paddle with walls and other paddles at [`GameStage::Paddle`] stage.
 
 use super::{
-    components::{BallCollider, BoundingBox, Paddle, Velocity, Wall},
-    resources::GameConfig,
+    components::{BallCollider, BoundingBox, Paddle, Player, Velocity, Wall},
+    level::level_params,
+    resources::{ActiveTheme, GameConfig, GameMode, Level, PlayerInputs},
     GameStage,
 };
 use bevy::{
@@ -22,56 +26,104 @@ impl Plugin for PaddlePlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system_to_stage(StartupStage::PostStartup, spawn_paddle_system)
             .add_system_to_stage(GameStage::Input, paddle_control_system)
-            .add_system_to_stage(GameStage::Paddle, paddle_wall_collision_system);
+            .add_system_to_stage(GameStage::Paddle, paddle_size_system)
+            .add_system_to_stage(
+                GameStage::Paddle,
+                paddle_paddle_collision_system.after(paddle_size_system),
+            )
+            // Last, the paddles pushed apart are already kept between the walls
+            .add_system_to_stage(
+                GameStage::Paddle,
+                paddle_wall_collision_system.after(paddle_paddle_collision_system),
+            );
+    }
+}
+
+/// Direction from the paddle of `player` toward the bricks along Y-axis,
+/// `1.0` at the bottom of the window and `-1.0` at the top.
+pub fn paddle_side(player: Player, cfg: &GameConfig) -> f32 {
+    if cfg.game_mode == GameMode::Versus && player.0 == 1 {
+        -1.
+    } else {
+        1.
     }
 }
 
-fn spawn_paddle_system(mut commands: Commands, cfg: Res<GameConfig>) {
+fn spawn_paddle_system(mut commands: Commands, theme: Res<ActiveTheme>, cfg: Res<GameConfig>) {
     let shape = shapes::Rectangle {
         extents: cfg.paddle_size,
         ..Default::default()
     };
 
-    commands
-        .spawn(GeometryBuilder::build_as(
-            &shape,
-            DrawMode::Outlined {
-                fill_mode: FillMode::color(Color::TURQUOISE),
-                outline_mode: StrokeMode::color(Color::BLACK),
-            },
-            Transform {
-                translation: Vec3::new(
-                    0.,
-                    -cfg.window_height / 2. + cfg.paddle_y_offset,
-                    cfg.paddle_z,
-                ),
-                ..Default::default()
-            },
-        ))
-        .insert(BallCollider)
-        .insert(Paddle)
-        .insert(Velocity(Vec2::ZERO))
-        .insert(BoundingBox(cfg.paddle_size));
+    let players = cfg.game_mode.players();
+    for player in (0..players).map(Player) {
+        let side = paddle_side(player, &cfg);
+        // Evenly spread on the same line
+        let x = match cfg.game_mode {
+            GameMode::Coop => {
+                (player.0 as f32 + 0.5) * cfg.window_width / players as f32 - cfg.window_width / 2.
+            }
+            _ => 0.,
+        };
+
+        commands
+            .spawn(GeometryBuilder::build_as(
+                &shape,
+                theme.0.outlined(theme.0.paddle),
+                Transform {
+                    translation: Vec3::new(
+                        x,
+                        side * (-cfg.window_height / 2. + cfg.paddle_y_offset),
+                        cfg.paddle_z,
+                    ),
+                    ..Default::default()
+                },
+            ))
+            .insert(BallCollider)
+            .insert(Paddle)
+            .insert(player)
+            .insert(Velocity(Vec2::ZERO))
+            .insert(BoundingBox(cfg.paddle_size));
+    }
 }
 
 fn paddle_control_system(
-    keys: Res<Input<KeyCode>>,
-    mut query: Query<&mut Velocity, With<Paddle>>,
+    inputs: Res<PlayerInputs>,
+    mut query: Query<(&mut Velocity, &Player), With<Paddle>>,
     cfg: Res<GameConfig>,
 ) {
-    let mut x = 0.;
+    let speed = cfg.paddle_base_speed * cfg.paddle_sensitivity;
 
-    if keys.pressed(KeyCode::Right) {
-        x += cfg.paddle_base_speed;
-    } else if keys.pressed(KeyCode::Left) {
-        x -= cfg.paddle_base_speed;
-    }
+    for (mut velocity, &player) in query.iter_mut() {
+        let input = inputs.get(player);
 
-    for mut velocity in query.iter_mut() {
+        let mut x = 0.;
+        if input.right {
+            x += speed;
+        } else if input.left {
+            x -= speed;
+        }
         velocity.0.x = x;
     }
 }
 
+fn paddle_size_system(
+    mut paddle_query: Query<(&mut Transform, &mut BoundingBox), With<Paddle>>,
+    level: Res<Level>,
+    cfg: Res<GameConfig>,
+) {
+    if !level.is_changed() && !cfg.is_changed() {
+        return;
+    }
+
+    let width = level_params(level.number, &cfg).paddle_width;
+    for (mut tf, mut bb) in paddle_query.iter_mut() {
+        // Scale rather than rebuild the shape, it works with skins too
+        tf.scale.x = width / cfg.paddle_size.x;
+        bb.0.x = width;
+    }
+}
+
 #[allow(clippy::type_complexity)]
 fn paddle_wall_collision_system(
     mut paddle_query: Query<
@@ -90,22 +142,115 @@ fn paddle_wall_collision_system(
 
         // Check collision with walls
         for (wall_tf, wall_bb) in wall_query.iter() {
-            let collision = collide(
+            stop_paddle_at_wall(
+                &mut paddle_tf.translation,
+                &mut paddle_v.0,
+                paddle_bb.0,
                 wall_tf.translation,
                 wall_bb.0,
-                paddle_tf.translation,
-                paddle_bb.0,
             );
+        }
+    }
+}
 
-            if let Some(Collision::Right) = collision {
-                paddle_v.0 = Vec2::ZERO;
-                paddle_tf.translation.x =
-                    wall_tf.translation.x - wall_bb.0.x / 2. - paddle_bb.0.x / 2.;
-            } else if let Some(Collision::Left) = collision {
-                paddle_v.0 = Vec2::ZERO;
-                paddle_tf.translation.x =
-                    wall_tf.translation.x + wall_bb.0.x / 2. + paddle_bb.0.x / 2.;
-            }
+/// Range of the center of a paddle of `paddle_size` at `paddle_y`, in the window and between the `walls` on its line.
+fn paddle_x_range(
+    paddle_y: f32,
+    paddle_size: Vec2,
+    walls: &[(Vec3, Vec2)],
+    cfg: &GameConfig,
+) -> (f32, f32) {
+    let mut range = (
+        -cfg.window_width / 2. + paddle_size.x / 2.,
+        cfg.window_width / 2. - paddle_size.x / 2.,
+    );
+    for &(wall_translation, wall_size) in walls {
+        if (wall_translation.y - paddle_y).abs() >= (wall_size.y + paddle_size.y) / 2. {
+            continue;
+        }
+        let distance = (wall_size.x + paddle_size.x) / 2.;
+        if wall_translation.x < 0. {
+            range.0 = range.0.max(wall_translation.x + distance);
+        } else {
+            range.1 = range.1.min(wall_translation.x - distance);
+        }
+    }
+    range
+}
+
+/// Push apart the paddles on the same line overlapping each other, each by half of the overlap.
+/// A paddle stopped by a wall doesn’t move, the other one is pushed by the whole overlap.
+#[allow(clippy::type_complexity)]
+fn paddle_paddle_collision_system(
+    mut paddle_query: Query<(&mut Transform, &BoundingBox, &mut Velocity), With<Paddle>>,
+    wall_query: Query<(&Transform, &BoundingBox), (With<Wall>, Without<Paddle>)>,
+    cfg: Res<GameConfig>,
+) {
+    let walls: Vec<(Vec3, Vec2)> = wall_query
+        .iter()
+        .map(|(wall_tf, wall_bb)| (wall_tf.translation, wall_bb.0))
+        .collect();
+
+    let mut combinations = paddle_query.iter_combinations_mut();
+    while let Some([(mut a_tf, a_bb, mut a_v), (mut b_tf, b_bb, mut b_v)]) =
+        combinations.fetch_next()
+    {
+        if (a_tf.translation.y - b_tf.translation.y).abs() >= (a_bb.0.y + b_bb.0.y) / 2. {
+            continue;
         }
+        let overlap = (a_bb.0.x + b_bb.0.x) / 2. - (a_tf.translation.x - b_tf.translation.x).abs();
+        if overlap <= 0. {
+            continue;
+        }
+
+        // From b toward a
+        let direction = if a_tf.translation.x < b_tf.translation.x {
+            -1.
+        } else {
+            1.
+        };
+        // Against each other, and both between the walls if they fit
+        let (a_min, a_max) = paddle_x_range(a_tf.translation.y, a_bb.0, &walls, &cfg);
+        let (b_min, b_max) = paddle_x_range(b_tf.translation.y, b_bb.0, &walls, &cfg);
+        let distance = (a_bb.0.x + b_bb.0.x) / 2.;
+        // Not `clamp`, which panics when a paddle is wider than the room between the walls
+        let a_x = (a_tf.translation.x + direction * overlap / 2.)
+            .max(a_min)
+            .min(a_max);
+        let b_x = (a_x - direction * distance).max(b_min).min(b_max);
+        a_tf.translation.x = (b_x + direction * distance).max(a_min).min(a_max);
+        b_tf.translation.x = b_x;
+
+        // Stop the paddles moving toward each other
+        if a_v.0.x * direction < 0. {
+            a_v.0.x = 0.;
+        }
+        if b_v.0.x * direction > 0. {
+            b_v.0.x = 0.;
+        }
+    }
+}
+
+/// Stop the paddle against a wall it collides with by its side.
+pub fn stop_paddle_at_wall(
+    paddle_translation: &mut Vec3,
+    paddle_velocity: &mut Vec2,
+    paddle_size: Vec2,
+    wall_translation: Vec3,
+    wall_size: Vec2,
+) {
+    let collision = collide(
+        wall_translation,
+        wall_size,
+        *paddle_translation,
+        paddle_size,
+    );
+
+    if let Some(Collision::Right) = collision {
+        *paddle_velocity = Vec2::ZERO;
+        paddle_translation.x = wall_translation.x - wall_size.x / 2. - paddle_size.x / 2.;
+    } else if let Some(Collision::Left) = collision {
+        *paddle_velocity = Vec2::ZERO;
+        paddle_translation.x = wall_translation.x + wall_size.x / 2. + paddle_size.x / 2.;
     }
 }
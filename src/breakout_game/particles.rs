@@ -0,0 +1,101 @@
+//! Particle related stuff.
+//!
+//!  - Spawn debris on [`BrickDestroyed`] at [`GameStage::Events`] stage, unless reduced motion is enabled.
+//!  - Apply gravity, fade and despawn particles at [`GameStage::Move`] stage.
+
+use super::{
+    components::{Particle, Velocity},
+    events::BrickDestroyed,
+    resources::GameConfig,
+    GameStage,
+};
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use rand::{thread_rng, Rng};
+use std::{f32::consts::TAU, time::Duration};
+
+/// Particle logic as a Bevy’s plugin.
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(GameStage::Events, spawn_debris_system)
+            .add_system_to_stage(GameStage::Move, update_particle_system);
+    }
+}
+
+fn spawn_debris_system(
+    mut commands: Commands,
+    mut brick_destroyed_events: EventReader<BrickDestroyed>,
+    particle_query: Query<&Particle>,
+    cfg: Res<GameConfig>,
+) {
+    if cfg.reduced_motion {
+        brick_destroyed_events.clear();
+        return;
+    }
+
+    let mut rng = thread_rng();
+    let mut particle_count = particle_query.iter().count();
+
+    let shape = shapes::Rectangle {
+        extents: Vec2::splat(cfg.particle_size),
+        ..Default::default()
+    };
+
+    for event in brick_destroyed_events.iter() {
+        // Never go over the limit, whatever the number of bricks destroyed at once
+        let count = (cfg.particles_per_brick as usize)
+            .min(cfg.particles_max.saturating_sub(particle_count));
+        particle_count += count;
+
+        for _ in 0..count {
+            let offset = Vec2::new(
+                rng.gen_range(-0.5..0.5) * cfg.brick_size.x,
+                rng.gen_range(-0.5..0.5) * cfg.brick_size.y,
+            );
+            let direction = Vec2::from_angle(rng.gen_range(0. ..TAU));
+            let speed = rng.gen_range(0.5..1.) * cfg.particle_speed;
+
+            commands
+                .spawn(GeometryBuilder::build_as(
+                    &shape,
+                    DrawMode::Fill(FillMode::color(event.color)),
+                    Transform {
+                        translation: (event.position + offset).extend(cfg.particle_z),
+                        rotation: Quat::from_rotation_z(rng.gen_range(0. ..TAU)),
+                        ..Default::default()
+                    },
+                ))
+                .insert(Particle {
+                    lifetime: Timer::from_seconds(cfg.particle_lifetime, TimerMode::Once),
+                })
+                .insert(Velocity(
+                    direction * speed + event.velocity.normalize_or_zero() * cfg.particle_speed,
+                ));
+        }
+    }
+}
+
+fn update_particle_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Particle, &mut Velocity, &mut DrawMode)>,
+    cfg: Res<GameConfig>,
+) {
+    // Same fixed step as the movement, the particles fade as far as they fall
+    let dt = cfg.time_step();
+
+    for (entity, mut particle, mut velocity, mut draw_mode) in query.iter_mut() {
+        particle.lifetime.tick(Duration::from_secs_f32(dt));
+        if particle.lifetime.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        velocity.0.y -= cfg.particle_gravity * dt;
+
+        if let DrawMode::Fill(fill_mode) = draw_mode.as_mut() {
+            fill_mode.color.set_a(particle.lifetime.percent_left());
+        }
+    }
+}
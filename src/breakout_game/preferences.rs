@@ -0,0 +1,162 @@
+//! User preferences, stored in a RON file of the user’s configuration directory.
+//!
+//! Preferences are separated from [`GameConfig`], which is defined by the game designer.
+//! They are merged over it when the game starts, unset preferences keep the designer’s value.
+
+use super::resources::{GameConfig, KeyBindings, LaunchMode};
+use bevy::log::warn;
+use serde::{Deserialize, Serialize};
+use std::{env, fs, path::PathBuf};
+
+/// Name of the preferences file.
+const PREFERENCES_FILE: &str = "preferences.ron";
+
+/// All the settings the player can change.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct UserPreferences {
+    /// See [`GameConfig::audio_volume`].
+    pub audio_volume: Option<f32>,
+    /// See [`GameConfig::audio_muted`].
+    pub audio_muted: Option<bool>,
+    /// See [`GameConfig::key_bindings`].
+    pub key_bindings: Option<KeyBindings>,
+    /// See [`GameConfig::ball_launch_mode`].
+    pub ball_launch_mode: Option<LaunchMode>,
+    /// See [`GameConfig::paddle_catch`].
+    pub paddle_catch: Option<bool>,
+    /// See [`GameConfig::paddle_sensitivity`].
+    pub paddle_sensitivity: Option<f32>,
+    /// See [`GameConfig::theme`].
+    pub theme: Option<usize>,
+    /// See [`GameConfig::color_blind_palette`].
+    pub color_blind_palette: Option<bool>,
+    /// See [`GameConfig::brick_glyphs`].
+    pub brick_glyphs: Option<bool>,
+    /// See [`GameConfig::high_contrast`].
+    pub high_contrast: Option<bool>,
+    /// See [`GameConfig::ui_scale`].
+    pub ui_scale: Option<f32>,
+    /// See [`GameConfig::reduced_motion`].
+    pub reduced_motion: Option<bool>,
+    /// See [`GameConfig::framerate`].
+    pub framerate: Option<f32>,
+    /// See [`GameConfig::fullscreen`].
+    pub fullscreen: Option<bool>,
+    /// See [`GameConfig::language`].
+    pub language: Option<String>,
+}
+
+/// Directory of the game in the user’s configuration directory, if it can be found.
+pub fn preferences_dir() -> Option<PathBuf> {
+    let config_dir = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+
+    config_dir.map(|dir| dir.join(env!("CARGO_PKG_NAME")))
+}
+
+impl UserPreferences {
+    /// Preferences with every setting taken from `cfg`.
+    pub fn from_config(cfg: &GameConfig) -> UserPreferences {
+        UserPreferences {
+            audio_volume: Some(cfg.audio_volume),
+            audio_muted: Some(cfg.audio_muted),
+            key_bindings: Some(cfg.key_bindings),
+            ball_launch_mode: Some(cfg.ball_launch_mode),
+            paddle_catch: Some(cfg.paddle_catch),
+            paddle_sensitivity: Some(cfg.paddle_sensitivity),
+            theme: Some(cfg.theme),
+            color_blind_palette: Some(cfg.color_blind_palette),
+            brick_glyphs: Some(cfg.brick_glyphs),
+            high_contrast: Some(cfg.high_contrast),
+            ui_scale: Some(cfg.ui_scale),
+            reduced_motion: Some(cfg.reduced_motion),
+            framerate: Some(cfg.framerate),
+            fullscreen: Some(cfg.fullscreen),
+            language: Some(cfg.language.clone()),
+        }
+    }
+
+    /// Override the settings of `cfg` with the preferences which are set.
+    pub fn apply(&self, cfg: &mut GameConfig) {
+        fn set<T: Copy>(value: Option<T>, target: &mut T) {
+            if let Some(value) = value {
+                *target = value;
+            }
+        }
+
+        set(self.audio_volume, &mut cfg.audio_volume);
+        set(self.audio_muted, &mut cfg.audio_muted);
+        set(self.key_bindings, &mut cfg.key_bindings);
+        set(self.ball_launch_mode, &mut cfg.ball_launch_mode);
+        set(self.paddle_catch, &mut cfg.paddle_catch);
+        set(self.paddle_sensitivity, &mut cfg.paddle_sensitivity);
+        set(self.theme, &mut cfg.theme);
+        set(self.color_blind_palette, &mut cfg.color_blind_palette);
+        set(self.brick_glyphs, &mut cfg.brick_glyphs);
+        set(self.high_contrast, &mut cfg.high_contrast);
+        set(self.ui_scale, &mut cfg.ui_scale);
+        set(self.reduced_motion, &mut cfg.reduced_motion);
+        set(self.framerate, &mut cfg.framerate);
+        set(self.fullscreen, &mut cfg.fullscreen);
+        if let Some(language) = &self.language {
+            cfg.language = language.clone();
+        }
+    }
+
+    /// These preferences with the settings changing the simulation taken from `other`.
+    ///
+    /// Both peers of a network game must run the same simulation, whatever their preferences.
+    pub fn with_simulation_of(self, other: &UserPreferences) -> UserPreferences {
+        UserPreferences {
+            ball_launch_mode: other.ball_launch_mode,
+            paddle_catch: other.paddle_catch,
+            paddle_sensitivity: other.paddle_sensitivity,
+            ..self
+        }
+    }
+
+    /// Load the preferences file, missing or invalid files give empty preferences.
+    pub fn load() -> UserPreferences {
+        let path = match preferences_dir() {
+            Some(dir) => dir.join(PREFERENCES_FILE),
+            None => return UserPreferences::default(),
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(content) => ron::from_str(&content).unwrap_or_else(|err| {
+                warn!("Invalid preferences file {}: {}", path.display(), err);
+                UserPreferences::default()
+            }),
+            Err(_) => UserPreferences::default(),
+        }
+    }
+
+    /// Write the preferences file, errors are logged.
+    pub fn save(&self) {
+        let dir = match preferences_dir() {
+            Some(dir) => dir,
+            None => {
+                warn!("No configuration directory to save the preferences");
+                return;
+            }
+        };
+
+        let result = fs::create_dir_all(&dir).and_then(|_| {
+            let content = ron::ser::to_string_pretty(self, Default::default())
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+            fs::write(dir.join(PREFERENCES_FILE), content)
+        });
+
+        if let Err(err) = result {
+            warn!("Cannot save the preferences in {}: {}", dir.display(), err);
+        }
+    }
+}
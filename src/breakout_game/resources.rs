@@ -1,18 +1,35 @@
 //! All the resources used by the game.
 
+use super::{
+    components::Player,
+    generator::GeneratorKind,
+    level::{DifficultyCurve, LevelDesign},
+    localization::Catalog,
+    skin::{SkinIndices, SkinManifest},
+    theme::Theme,
+};
 use bevy::{
-    prelude::{Color, Handle, Resource, Vec2},
+    prelude::{AudioSource, Entity, Handle, IVec2, Image, KeyCode, Resource, TextureAtlas, Vec2},
     text::Font,
-    time::Timer,
+    time::{Stopwatch, Timer},
+    utils::HashMap,
 };
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Configuration used by the game.
 ///
 /// Use the same definition for pixel than Bevy.
 #[derive(Resource, Clone)]
 pub struct GameConfig {
-    /// Expected framerate.
+    /// Framerate cap, the game’s speed doesn’t depend on it, see [`GameConfig::simulation_rate`].
     pub framerate: f32,
+    /// Ticks of the simulation per second, each one advances the gameplay by [`GameConfig::time_step`].
+    /// A single tick is run per frame, the game slows down if the frames are slower than the ticks.
+    pub simulation_rate: f32,
+    /// If true, the window is borderless fullscreen.
+    pub fullscreen: bool,
 
     /// Window’s width in pixels.
     pub window_width: f32,
@@ -20,8 +37,8 @@ pub struct GameConfig {
     /// Window’s height in pixels.
     pub window_height: f32,
 
-    /// Window’s background color.
-    pub background_color: Color,
+    /// Solo or two players, the paddles and the walls are spawned once for the mode of the game’s start.
+    pub game_mode: GameMode,
 
     /// Ball position along Z-axis in pixels.
     pub ball_z: f32,
@@ -31,6 +48,10 @@ pub struct GameConfig {
     pub paddle_z: f32,
     /// Walls position along Z-axis in pixels.
     pub wall_z: f32,
+    /// Particles position along Z-axis in pixels.
+    pub particle_z: f32,
+    /// Ball trails position along Z-axis in pixels.
+    pub trail_z: f32,
 
     /// Wall’s thickness in pixels.
     pub wall_thickness: f32,
@@ -39,8 +60,14 @@ pub struct GameConfig {
     pub paddle_size: Vec2,
     /// Paddle’s speed in pixels/second.
     pub paddle_base_speed: f32,
+    /// Multiplier applied to the paddle’s speed.
+    pub paddle_sensitivity: f32,
     /// Paddle’s position along Y-axis as an offset from the bottom of the window in pixels.
     pub paddle_y_offset: f32,
+    /// If true, the paddle catches the ball, see [`super::ball::CATCH_EFFECT`] to enable it temporarily.
+    pub paddle_catch: bool,
+    /// Duration in seconds of the [`super::ball::CATCH_EFFECT`] granted when a strong brick is destroyed, never granted if None.
+    pub catch_effect_duration: Option<f32>,
 
     /// Ball’s radius in pixels.
     pub ball_radius: f32,
@@ -49,6 +76,25 @@ pub struct GameConfig {
     /// Ball’s position along Y-axis as an offset from the paddle in pixels.
     /// Zero means the ball start on the paddle.
     pub ball_y_offset: f32,
+    /// Speed added by each speed-up, in fraction of the level’s ball speed.
+    pub ball_speed_up_step: f32,
+    /// Number of paddle hits to speed up the ball, 0 to disable this speed-up.
+    pub ball_speed_up_paddle_hits: u32,
+    /// Number of rows at the top of the brick’s spawning area to speed up the ball when first reached,
+    /// 0 to disable this speed-up.
+    pub ball_speed_up_back_rows: u32,
+    /// If true, the ball speeds up when it destroys a [`super::components::BrickKind::Strong`] brick.
+    pub ball_speed_up_strong_bricks: bool,
+    /// Maximum ball’s speed, in fraction of the level’s ball speed.
+    pub ball_speed_max: f32,
+    /// How the direction of the ball is chosen when it is thrown.
+    pub ball_launch_mode: LaunchMode,
+    /// Maximum angle between the direction of a thrown ball and the vertical in degrees.
+    pub ball_launch_max_angle: f32,
+    /// Aiming speed in degrees per second, see [`LaunchMode::Aimed`].
+    pub ball_aim_speed: f32,
+    /// Seconds before the ball is thrown automatically, `None` to wait for the player.
+    pub ball_auto_launch_delay: Option<f32>,
 
     /// Brick’s size in pixels.
     pub brick_size: Vec2,
@@ -61,99 +107,425 @@ pub struct GameConfig {
     pub brick_area_rows: u32,
     /// Number of columns of the brick’s spawning area.
     pub brick_area_cols: u32,
+    /// How the ball, the paddle and the bricks change from one level to the next.
+    pub difficulty_curve: DifficultyCurve,
+    /// Generator of all the levels, replaces the generators of the [`DifficultyCurve`] if any.
+    pub level_generator: Option<GeneratorKind>,
+    /// Minimum number of bricks of a generated level.
+    pub level_min_bricks: u32,
+    /// If true, every empty cell of a generated level is reachable by the ball without breaking bricks.
+    pub level_reachable: bool,
+    /// Size of the cells of the [`Broadphase`] grid in pixels, about the size of a brick.
+    pub broadphase_cell_size: f32,
 
     /// Initial score when the game start.
     pub startup_score: i32,
     /// Score gain for breaking a brick.
     pub score_brick: i32,
+    /// Score gain for sending the ball past the opponent, see [`GameMode::Versus`].
+    pub score_goal: i32,
+    /// Score to win a game, see [`GameMode::Versus`].
+    pub versus_winning_score: i32,
     /// Path to the font file used to render score in game.
     pub score_font_path: String,
     /// Font size of the in game score text.
     pub score_font_size: f32,
+    /// Font size of the other HUD texts.
+    pub hud_font_size: f32,
+    /// Speed at which the displayed score reaches the actual score, in fraction of the difference per second.
+    pub hud_score_count_up_rate: f32,
+    /// Maximum number of life icons in the HUD.
+    pub hud_max_life_icons: u32,
+    /// Duration of the HUD banners (game over, etc.) in seconds.
+    pub hud_banner_duration: f32,
+
+    /// Number of lives when the game starts.
+    pub startup_lives: u32,
+
+    /// Volume of the sound effects, `1.0` is the volume of the synthesized sounds.
+    pub audio_volume: f32,
+    /// If true, no sound effect is played.
+    pub audio_muted: bool,
+
+    /// Number of debris spawned when a brick is destroyed.
+    pub particles_per_brick: u32,
+    /// Maximum number of particles alive at the same time.
+    pub particles_max: usize,
+    /// Particle’s size in pixels.
+    pub particle_size: f32,
+    /// Particle’s maximum initial speed in pixels/second.
+    pub particle_speed: f32,
+    /// Particle’s lifetime in seconds.
+    pub particle_lifetime: f32,
+    /// Gravity applied to particles in pixels/second².
+    pub particle_gravity: f32,
+
+    /// Camera shake trauma added when a brick is destroyed, in range `0.0..=1.0`.
+    pub camera_shake_brick: f32,
+    /// Camera’s offset at maximum trauma in pixels.
+    pub camera_shake_max_offset: f32,
+    /// Trauma removed every second.
+    pub camera_shake_decay: f32,
+
+    /// If false, balls do not leave a trail.
+    pub trail_enabled: bool,
+    /// Number of recent positions drawn behind a ball at full speed.
+    pub trail_max_length: usize,
+    /// Ball’s speed giving the longest trail, in pixels/second.
+    pub trail_fast_speed: f32,
+
+    /// Paths to additional theme files, after the built-in themes.
+    pub theme_paths: Vec<String>,
+
+    /// If true, bricks use a palette safe for color vision deficiency.
+    pub color_blind_palette: bool,
+    /// If true, a glyph is drawn over each brick to tell them apart without colors.
+    pub brick_glyphs: bool,
+    /// If true, the theme is replaced by high-contrast colors.
+    pub high_contrast: bool,
+    /// Scale factor applied to the texts, `1.0` is the original size.
+    pub ui_scale: f32,
+    /// If true, particles and camera shake are disabled.
+    pub reduced_motion: bool,
 
-    /// Possibles colors for bricks, chosen at random.
-    pub brick_colors: Vec<Color>,
+    /// Path to the skin manifest, vector shapes are used if none.
+    pub skin_path: Option<String>,
+
+    /// Index of the theme used when the game starts, see [`Themes::handles`].
+    pub theme: usize,
+
+    /// Keys used to play.
+    pub key_bindings: KeyBindings,
+
+    /// Code of the language of the texts, English is used if there is no catalog for it.
+    pub language: String,
+
+    /// Seed of the gameplay’s random number generator, a random seed is used if none.
+    pub seed: Option<u64>,
+    /// If true, the game is saved when the window is closed and resumed when the game starts.
+    pub resume_saved_game: bool,
+
+    /// If true, the level editor is open when the game starts and can be toggled in release builds.
+    pub level_editor: bool,
+    /// Path of the level file saved and loaded by the level editor.
+    pub editor_level_path: String,
+
+    /// Play with a remote peer, see [`super::net`].
+    pub net: Option<NetConfig>,
+    /// Publish the gameplay state on a local socket, see [`super::telemetry`].
+    pub telemetry: Option<TelemetryConfig>,
+}
+
+impl GameConfig {
+    /// Fixed time step of a tick of the simulation in seconds.
+    pub fn time_step(&self) -> f32 {
+        1. / self.simulation_rate
+    }
 }
 
 impl Default for GameConfig {
     fn default() -> GameConfig {
         GameConfig {
             framerate: 60.,
+            simulation_rate: 60.,
+            fullscreen: false,
 
             window_width: 430.,
             window_height: 600.,
-            background_color: Color::rgb(0.21, 0.19, 0.17),
+
+            game_mode: GameMode::default(),
 
             ball_z: 1.,
             brick_z: 3.,
             paddle_z: 2.,
             wall_z: 4.,
+            particle_z: 5.,
+            trail_z: 0.5,
 
             wall_thickness: 5.,
 
             paddle_size: Vec2::new(80., 15.),
             paddle_base_speed: 400.,
+            paddle_sensitivity: 1.,
             paddle_y_offset: 30.,
+            paddle_catch: false,
+            catch_effect_duration: None,
 
             ball_radius: 7.,
             ball_base_speed: 550.,
             ball_y_offset: 5.,
+            ball_speed_up_step: 0.1,
+            ball_speed_up_paddle_hits: 4,
+            ball_speed_up_back_rows: 2,
+            ball_speed_up_strong_bricks: true,
+            ball_speed_max: 1.5,
+            ball_launch_mode: LaunchMode::default(),
+            ball_launch_max_angle: 30.,
+            ball_aim_speed: 60.,
+            ball_auto_launch_delay: None,
 
             brick_size: Vec2::new(40., 20.),
             brick_spawn_probability: 0.2,
             brick_area_y_offset: 100.,
             brick_area_rows: 12,
             brick_area_cols: 6,
+            difficulty_curve: DifficultyCurve::default(),
+            level_generator: None,
+            level_min_bricks: 6,
+            level_reachable: true,
+            broadphase_cell_size: 40.,
 
             startup_score: 0,
             score_brick: 1,
+            score_goal: 5,
+            versus_winning_score: 30,
             score_font_path: "fonts/arial.ttf".to_string(),
             score_font_size: 32.,
+            hud_font_size: 18.,
+            hud_score_count_up_rate: 8.,
+            hud_max_life_icons: 5,
+            hud_banner_duration: 2.,
+
+            startup_lives: 3,
+
+            audio_volume: 0.5,
+            audio_muted: false,
+
+            particles_per_brick: 8,
+            particles_max: 200,
+            particle_size: 4.,
+            particle_speed: 150.,
+            particle_lifetime: 0.6,
+            particle_gravity: 600.,
+
+            camera_shake_brick: 0.3,
+            camera_shake_max_offset: 6.,
+            camera_shake_decay: 2.,
+
+            trail_enabled: true,
+            trail_max_length: 8,
+            trail_fast_speed: 800.,
+
+            theme_paths: Vec::new(),
+
+            color_blind_palette: false,
+            brick_glyphs: false,
+            high_contrast: false,
+            ui_scale: 1.,
+            reduced_motion: false,
+
+            skin_path: None,
+
+            theme: 0,
+
+            key_bindings: KeyBindings::default(),
+
+            language: "en".to_string(),
+
+            seed: None,
+            resume_saved_game: false,
+
+            level_editor: false,
+            editor_level_path: "assets/levels/custom.level.ron".to_string(),
 
-            brick_colors: vec![
-                Color::ALICE_BLUE,
-                Color::ANTIQUE_WHITE,
-                Color::AQUAMARINE,
-                Color::AZURE,
-                Color::BEIGE,
-                Color::BISQUE,
-                Color::BLACK,
-                Color::BLUE,
-                Color::CRIMSON,
-                Color::CYAN,
-                Color::DARK_GRAY,
-                Color::DARK_GREEN,
-                Color::FUCHSIA,
-                Color::GOLD,
-                Color::GRAY,
-                Color::GREEN,
-                Color::INDIGO,
-                Color::LIME_GREEN,
-                Color::MAROON,
-                Color::MIDNIGHT_BLUE,
-                Color::NAVY,
-                // Color::NONE,
-                Color::OLIVE,
-                Color::ORANGE,
-                Color::ORANGE_RED,
-                Color::PINK,
-                Color::PURPLE,
-                Color::RED,
-                Color::SALMON,
-                Color::SEA_GREEN,
-                Color::SILVER,
-                Color::TEAL,
-                Color::TOMATO,
-                Color::TURQUOISE,
-                Color::VIOLET,
-                Color::WHITE,
-                Color::YELLOW,
-                Color::YELLOW_GREEN,
-            ],
+            net: None,
+            telemetry: None,
         }
     }
 }
 
+/// Keys used to play.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct KeyBindings {
+    /// Move the paddle to the left.
+    pub left: KeyCode,
+    /// Move the paddle to the right.
+    pub right: KeyCode,
+    /// Throw the ball.
+    pub launch: KeyCode,
+    /// Aim to the left before throwing the ball, see [`LaunchMode::Aimed`].
+    pub aim_left: KeyCode,
+    /// Aim to the right before throwing the ball, see [`LaunchMode::Aimed`].
+    pub aim_right: KeyCode,
+    /// Save the game in progress, see [`super::save`].
+    pub save_game: KeyCode,
+    /// Resume the saved game, see [`super::save`].
+    pub load_game: KeyCode,
+    /// Open or close the level editor, see [`super::editor`].
+    pub editor: KeyCode,
+    /// Move the second player’s paddle to the left, see [`GameMode`].
+    pub second_left: KeyCode,
+    /// Move the second player’s paddle to the right, see [`GameMode`].
+    pub second_right: KeyCode,
+    /// Throw the second player’s ball, see [`GameMode`].
+    pub second_launch: KeyCode,
+    /// Show or hide the debug overlay, only built with the `debug` feature.
+    pub debug_overlay: KeyCode,
+    /// Pause or resume the game from the debug overlay.
+    pub debug_pause: KeyCode,
+    /// Run a single frame while paused, from the debug overlay.
+    pub debug_step: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings {
+            left: KeyCode::Left,
+            right: KeyCode::Right,
+            launch: KeyCode::Space,
+            aim_left: KeyCode::A,
+            aim_right: KeyCode::D,
+            save_game: KeyCode::F5,
+            load_game: KeyCode::F9,
+            editor: KeyCode::F2,
+            second_left: KeyCode::Z,
+            second_right: KeyCode::C,
+            second_launch: KeyCode::X,
+            debug_overlay: KeyCode::F3,
+            debug_pause: KeyCode::F4,
+            debug_step: KeyCode::F6,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Keys moving the paddle of `player` to the left and to the right.
+    pub fn paddle_keys(&self, player: Player) -> (KeyCode, KeyCode) {
+        match player.0 {
+            0 => (self.left, self.right),
+            _ => (self.second_left, self.second_right),
+        }
+    }
+
+    /// Key throwing the balls of `player`.
+    pub fn launch_key(&self, player: Player) -> KeyCode {
+        match player.0 {
+            0 => self.launch,
+            _ => self.second_launch,
+        }
+    }
+}
+
+/// Network play with a remote peer, see [`super::net`].
+#[derive(Clone, Debug)]
+pub struct NetConfig {
+    /// Local address of the UDP socket.
+    pub local_addr: String,
+    /// Address of the remote peer.
+    pub peer_addr: String,
+    /// The local player, the remote peer plays the other one.
+    pub local_player: Player,
+    /// Number of ticks the local inputs are delayed by, to hide the latency.
+    pub input_delay: u32,
+    /// Simulated conditions of the link, to test on one machine.
+    pub link: LinkConditions,
+}
+
+impl Default for NetConfig {
+    fn default() -> NetConfig {
+        NetConfig {
+            local_addr: "0.0.0.0:7000".to_string(),
+            peer_addr: "127.0.0.1:7001".to_string(),
+            local_player: Player(0),
+            input_delay: 3,
+            link: LinkConditions::default(),
+        }
+    }
+}
+
+/// Telemetry server publishing the gameplay state, see [`super::telemetry`].
+#[derive(Clone, Debug)]
+pub struct TelemetryConfig {
+    /// TCP address, or path of a Unix socket prefixed by `unix:`.
+    pub address: String,
+    /// If true, the clients can move the paddles.
+    pub control: bool,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> TelemetryConfig {
+        TelemetryConfig {
+            address: "127.0.0.1:7100".to_string(),
+            control: false,
+        }
+    }
+}
+
+/// Simulated conditions of a network link, the default is a perfect link.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LinkConditions {
+    /// Probability of a packet to be lost, in range `0.0..=1.0`.
+    pub loss: f64,
+    /// Delay added to every packet.
+    pub latency: Duration,
+    /// Maximum random delay added to the latency, packets may arrive out of order.
+    pub jitter: Duration,
+}
+
+/// Input of a player for a frame of the simulation.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PlayerInput {
+    /// Move the paddle to the left.
+    pub left: bool,
+    /// Move the paddle to the right.
+    pub right: bool,
+    /// Throw a ball, only on the frame of the key press.
+    pub launch: bool,
+    /// Aim to the left, see [`LaunchMode::Aimed`].
+    pub aim_left: bool,
+    /// Aim to the right, see [`LaunchMode::Aimed`].
+    pub aim_right: bool,
+}
+
+/// Inputs of all the players for the current frame, indexed by [`Player`], see [`super::controls`].
+#[derive(Resource, Default)]
+pub struct PlayerInputs(pub Vec<PlayerInput>);
+
+impl PlayerInputs {
+    /// Input of `player`, nothing pressed if unknown.
+    pub fn get(&self, player: Player) -> PlayerInput {
+        self.0.get(player.0).copied().unwrap_or_default()
+    }
+}
+
+/// Number of players and how they play.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameMode {
+    /// A single player at the bottom of the window.
+    #[default]
+    Solo,
+    /// Two players, one at the bottom and one at the top of the window, with the bricks in the middle as shields.
+    /// There is no top wall, a player scores [`GameConfig::score_goal`] when the ball passes the opponent’s paddle,
+    /// the first to reach [`GameConfig::versus_winning_score`] wins.
+    Versus,
+    /// Two players side by side at the bottom of the window, sharing the lives.
+    /// Each player scores the bricks destroyed by the ball they hit last.
+    Coop,
+}
+
+impl GameMode {
+    /// Number of players, and of paddles.
+    pub fn players(self) -> usize {
+        match self {
+            GameMode::Solo => 1,
+            GameMode::Versus | GameMode::Coop => 2,
+        }
+    }
+}
+
+/// How the direction of the ball is chosen when it is thrown.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LaunchMode {
+    /// At random, within [`GameConfig::ball_launch_max_angle`].
+    #[default]
+    Random,
+    /// By the player with [`KeyBindings::aim_left`] and [`KeyBindings::aim_right`].
+    Aimed,
+    /// Following the paddle’s motion, to the right if the paddle moves to the right.
+    PaddleMotion,
+}
+
 /// Indicate if bricks should be spawned or not.
 #[derive(Resource)]
 pub struct BrickRespawn {
@@ -171,3 +543,188 @@ pub struct GameAssets {
     /// Font used to render score in game.
     pub score_font: Handle<Font>,
 }
+
+/// Store all the synthesized sound effects.
+#[derive(Resource)]
+pub struct SoundEffects {
+    /// Played when a ball bounces on a wall.
+    pub wall_hit: Handle<AudioSource>,
+    /// Played when a ball bounces on a paddle.
+    pub paddle_hit: Handle<AudioSource>,
+    /// Played when a ball destroys a brick, one sound per row of the brick’s spawning area.
+    pub brick_destroyed: Vec<Handle<AudioSource>>,
+    /// Played when a ball goes out of the window.
+    pub ball_lost: Handle<AudioSource>,
+}
+
+/// Current camera shake.
+#[derive(Resource, Default)]
+pub struct CameraShake {
+    /// Intensity of the shake in range `0.0..=1.0`, decreases over time.
+    pub trauma: f32,
+}
+
+/// The theme currently used to color the game.
+#[derive(Resource)]
+pub struct ActiveTheme(pub Theme);
+
+/// All the available themes.
+#[derive(Resource)]
+pub struct Themes {
+    /// Built-in themes followed by the themes of [`GameConfig::theme_paths`].
+    pub handles: Vec<Handle<Theme>>,
+    /// Index of the active theme in [`Themes::handles`].
+    pub active: usize,
+}
+
+/// Message catalogs of all languages.
+#[derive(Resource)]
+pub struct Localization {
+    /// Built-in catalogs, the first one is the English fallback.
+    pub catalogs: Vec<Catalog>,
+    /// Index of the catalog of [`GameConfig::language`] in [`Localization::catalogs`].
+    pub active: usize,
+}
+
+/// The skin replacing the vector shapes, only present if [`GameConfig::skin_path`] is set.
+#[derive(Resource)]
+pub struct Skin {
+    /// Manifest describing the skin.
+    pub manifest: Handle<SkinManifest>,
+    /// Texture of the skin, loaded once the manifest is loaded.
+    pub texture: Option<Handle<Image>>,
+    /// Atlas built once the texture is loaded.
+    pub atlas: Option<Handle<TextureAtlas>>,
+    /// Atlas indices of the skin’s regions.
+    pub indices: SkinIndices,
+}
+
+/// Remaining lives, the game is over when there is none.
+#[derive(Resource)]
+pub struct Lives(pub u32);
+
+/// Best score since the game started.
+#[derive(Resource, Default)]
+pub struct HighScore {
+    /// Best score.
+    pub score: i32,
+    /// Level reached with the best score.
+    pub level: u32,
+}
+
+/// Current level.
+#[derive(Resource)]
+pub struct Level {
+    /// Level number, starting at 1.
+    pub number: u32,
+    /// Level name, may be empty.
+    pub name: String,
+}
+
+impl Default for Level {
+    fn default() -> Level {
+        Level {
+            number: 1,
+            name: String::new(),
+        }
+    }
+}
+
+/// Elapsed time since the beginning of the session.
+#[derive(Resource, Default)]
+pub struct ElapsedTime(pub Stopwatch);
+
+/// A temporary effect, such as a power-up.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ActiveEffect {
+    /// Name displayed in the HUD.
+    pub name: String,
+    /// The effect ends at the end of the timer.
+    pub timer: Timer,
+}
+
+/// All the active temporary effects.
+#[derive(Resource, Default)]
+pub struct ActiveEffects(pub Vec<ActiveEffect>);
+
+/// State of the HUD animations.
+#[derive(Resource, Default)]
+pub struct HudState {
+    /// Score currently displayed, counting up toward the actual score.
+    pub displayed_score: f32,
+    /// Text of the banner displayed in the middle of the playfield.
+    pub banner: String,
+    /// Remaining time of the banner, hidden once finished.
+    pub banner_timer: Timer,
+}
+
+/// If true, the gameplay stages are not run, see [`super::GameStage`].
+#[derive(Resource, Default)]
+pub struct Paused(pub bool);
+
+/// If true, the gameplay stages are not run while waiting for the inputs of a remote player, see [`super::net`].
+#[derive(Resource, Default)]
+pub struct Stalled(pub bool);
+
+/// Time accumulated toward the next tick of the simulation, see [`GameConfig::simulation_rate`].
+#[derive(Resource, Default)]
+pub struct SimulationClock {
+    /// Time in seconds, negative if the simulation is ahead of the frames.
+    pub accumulator: f32,
+    /// If true, the gameplay stages run a tick in the current frame.
+    pub tick: bool,
+}
+
+/// State of the settings menu.
+#[derive(Resource, Default)]
+pub struct SettingsMenu {
+    /// If true, the menu is displayed and the game is paused.
+    pub open: bool,
+    /// Index of the selected item.
+    pub selected: usize,
+    /// If true, the next key pressed is bound to the selected item.
+    pub rebinding: bool,
+}
+
+/// Uniform grid of the entities marked with [`super::components::BallCollider`],
+/// to only check the collisions of a ball with the nearby colliders.
+#[derive(Resource, Default)]
+pub struct Broadphase {
+    /// Size of the cells in pixels, see [`GameConfig::broadphase_cell_size`].
+    pub cell_size: f32,
+    /// Colliders overlapping each cell, by cell coordinates.
+    pub cells: HashMap<IVec2, Vec<Entity>>,
+    /// First and last cells overlapped by each collider.
+    pub entries: HashMap<Entity, (IVec2, IVec2)>,
+}
+
+/// Random number generator of the gameplay, saved with the game to resume the same simulation.
+///
+/// Purely visual randomness, like particles, doesn’t use it.
+#[derive(Resource, Clone)]
+pub struct GameRng(pub ChaCha8Rng);
+
+/// Bricks of the levels placed by hand rather than at random, see [`super::brick`].
+#[derive(Resource, Default)]
+pub struct CustomLevel(pub Option<LevelDesign>);
+
+/// State of the level editor.
+#[derive(Resource)]
+pub struct Editor {
+    /// If true, the editor is displayed and the game is paused.
+    pub open: bool,
+    /// The level being edited.
+    pub design: LevelDesign,
+    /// Hit points of the painted bricks.
+    pub hit_points: u32,
+    /// Index of the color of the painted bricks in the theme’s palette, the theme’s color if none.
+    pub color: Option<usize>,
+    /// Previous designs, the last one is restored by undo.
+    pub undo: Vec<LevelDesign>,
+    /// Undone designs, the last one is restored by redo.
+    pub redo: Vec<LevelDesign>,
+    /// Design before the mouse button was pressed, a stroke is undone at once.
+    pub stroke: Option<LevelDesign>,
+    /// Result of the last action on the level file, displayed to the designer.
+    pub message: String,
+}
@@ -0,0 +1,342 @@
+//! Save and resume a game in progress.
+//!
+//! The whole gameplay state is written in a versioned RON file of the user’s configuration directory:
+//! bricks, scores, lives, level, paddles, balls, brick respawn timer and [`GameRng`].
+//! Resuming a [`SavedGame`] restores an identical simulation.
+//!
+//!  - Resume the saved game when the game starts or when [`KeyBindings::load_game`] is pressed
+//!    at [`CoreStage::PreUpdate`] stage.
+//!  - Save the game when [`KeyBindings::save_game`] is pressed or when the game is closed
+//!    at [`CoreStage::Last`] stage.
+//!
+//! Saving on close and resuming on start depend on [`GameConfig::resume_saved_game`].
+//!
+//! [`KeyBindings::load_game`]: super::resources::KeyBindings::load_game
+//! [`KeyBindings::save_game`]: super::resources::KeyBindings::save_game
+
+use super::{
+    ball::spawn_ball,
+    brick::{spawn_brick, BrickState},
+    components::{
+        Ball, BallLaunch, BallSpeed, Brick, BrickCell, BrickColor, BrickHitPoints, BrickKind,
+        BrickMotion, CaughtBall, FlyingBall, Paddle, Player, Score, StationaryBall, Velocity,
+    },
+    preferences::preferences_dir,
+    resources::{
+        ActiveEffect, ActiveEffects, ActiveTheme, BrickRespawn, ElapsedTime, GameConfig, GameRng,
+        Level, Lives,
+    },
+};
+use bevy::{app::AppExit, ecs::system::CommandQueue, prelude::*, time::Stopwatch};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+/// Name of the save file.
+const SAVE_FILE: &str = "savegame.ron";
+
+/// Version of the save file format, files of other versions are ignored.
+pub const SAVE_VERSION: u32 = 2;
+
+/// Save logic as a Bevy’s plugin.
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(CoreStage::PreUpdate, load_game_system.at_end())
+            .add_system_to_stage(CoreStage::Last, save_game_system);
+    }
+}
+
+/// The whole gameplay state.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedGame {
+    /// Format of the file, see [`SAVE_VERSION`].
+    pub version: u32,
+    /// Score of each player, in order.
+    pub scores: Vec<i32>,
+    /// Remaining lives.
+    pub lives: u32,
+    /// Level number.
+    pub level: u32,
+    /// Level name.
+    pub level_name: String,
+    /// Elapsed time since the beginning of the session.
+    pub elapsed_time: Stopwatch,
+    /// Active temporary effects.
+    pub effects: Vec<ActiveEffect>,
+    /// All the bricks, sorted by cell.
+    pub bricks: Vec<BrickState>,
+    /// All the paddles.
+    pub paddles: Vec<SavedPaddle>,
+    /// All the balls.
+    pub balls: Vec<SavedBall>,
+    /// See [`BrickRespawn::immediate_spawn`].
+    pub brick_immediate_spawn: bool,
+    /// See [`BrickRespawn::timer`].
+    pub brick_respawn_timer: Timer,
+    /// State of the gameplay’s random number generator.
+    pub rng: ChaCha8Rng,
+}
+
+/// State of a paddle, its size depends on the level.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedPaddle {
+    /// Player controlling the paddle.
+    pub player: Player,
+    /// Position of the paddle’s center.
+    pub translation: Vec3,
+    /// Velocity in pixels per second.
+    pub velocity: Vec2,
+}
+
+/// State of a ball.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedBall {
+    /// Player who hit the ball last, or who throws it.
+    pub player: Player,
+    /// Position of the ball’s center.
+    pub translation: Vec3,
+    /// Velocity in pixels per second.
+    pub velocity: Vec2,
+    /// Speed of the ball and progress of its speed-up rules.
+    pub speed: BallSpeed,
+    /// Direction and timer of the next throw.
+    pub launch: BallLaunch,
+    /// If true, the ball is a [`FlyingBall`], otherwise a [`StationaryBall`].
+    pub flying: bool,
+    /// Contact point on the paddle, if the ball has been caught.
+    pub caught: Option<CaughtBall>,
+}
+
+/// Only the version, read first to ignore incompatible files.
+#[derive(Deserialize)]
+struct SaveVersion {
+    version: u32,
+}
+
+/// Path of the save file, if the user’s configuration directory can be found.
+fn save_path() -> Option<PathBuf> {
+    preferences_dir().map(|dir| dir.join(SAVE_FILE))
+}
+
+impl SavedGame {
+    /// Capture the gameplay state of `world`.
+    pub fn capture(world: &mut World) -> SavedGame {
+        let mut bricks: Vec<BrickState> = world
+            .query_filtered::<(
+                &Transform,
+                &BrickCell,
+                &BrickKind,
+                &BrickColor,
+                &BrickHitPoints,
+                Option<&BrickMotion>,
+            ), With<Brick>>()
+            .iter(world)
+            .map(|(tf, &cell, &kind, color, hit_points, motion)| BrickState {
+                translation: tf.translation,
+                cell,
+                kind,
+                color: color.0,
+                hit_points: hit_points.0,
+                motion: motion.copied(),
+            })
+            .collect();
+        // Same file whatever the order of the entities
+        bricks.sort_by_key(|brick| (brick.cell.row, brick.cell.col));
+
+        let mut paddles: Vec<SavedPaddle> = world
+            .query_filtered::<(&Transform, &Velocity, &Player), With<Paddle>>()
+            .iter(world)
+            .map(|(tf, velocity, &player)| SavedPaddle {
+                player,
+                translation: tf.translation,
+                velocity: velocity.0,
+            })
+            .collect();
+        paddles.sort_by_key(|paddle| paddle.player.0);
+
+        let balls = world
+            .query_filtered::<(
+                &Transform,
+                &Velocity,
+                &BallSpeed,
+                &BallLaunch,
+                &Player,
+                Option<&FlyingBall>,
+                Option<&CaughtBall>,
+            ), With<Ball>>()
+            .iter(world)
+            .map(
+                |(tf, velocity, &speed, launch, &player, flying, caught)| SavedBall {
+                    player,
+                    translation: tf.translation,
+                    velocity: velocity.0,
+                    speed,
+                    launch: launch.clone(),
+                    flying: flying.is_some(),
+                    caught: caught.copied(),
+                },
+            )
+            .collect();
+
+        let mut scores: Vec<(Player, i32)> = world
+            .query::<(&Score, &Player)>()
+            .iter(world)
+            .map(|(score, &player)| (player, score.0))
+            .collect();
+        scores.sort_by_key(|&(player, _)| player.0);
+        let level = world.resource::<Level>();
+        let brick_respawn = world.resource::<BrickRespawn>();
+
+        SavedGame {
+            version: SAVE_VERSION,
+            scores: scores.into_iter().map(|(_, score)| score).collect(),
+            lives: world.resource::<Lives>().0,
+            level: level.number,
+            level_name: level.name.clone(),
+            elapsed_time: world.resource::<ElapsedTime>().0.clone(),
+            effects: world.resource::<ActiveEffects>().0.clone(),
+            bricks,
+            paddles,
+            balls,
+            brick_immediate_spawn: brick_respawn.immediate_spawn,
+            brick_respawn_timer: brick_respawn.timer.clone(),
+            rng: world.resource::<GameRng>().0.clone(),
+        }
+    }
+
+    /// Replace the gameplay state of `world`, bricks and balls are respawned.
+    pub fn restore(&self, world: &mut World) {
+        let theme = world.resource::<ActiveTheme>().0.clone();
+        let cfg = world.resource::<GameConfig>().clone();
+        let despawned: Vec<Entity> = world
+            .query_filtered::<Entity, Or<(With<Brick>, With<Ball>)>>()
+            .iter(world)
+            .collect();
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, world);
+        for entity in despawned {
+            // Recursive to despawn the glyphs and the aiming arrows too
+            commands.entity(entity).despawn_recursive();
+        }
+        for brick in self.bricks.iter() {
+            spawn_brick(&mut commands, brick, &theme, &cfg);
+        }
+        for ball in self.balls.iter() {
+            let entity = spawn_ball(&mut commands, &theme, &cfg, ball.player);
+            let mut ball_commands = commands.entity(entity);
+            ball_commands
+                .insert(Transform::from_translation(ball.translation))
+                .insert(Velocity(ball.velocity))
+                .insert(ball.speed)
+                .insert(ball.launch.clone());
+            if ball.flying {
+                ball_commands.remove::<StationaryBall>().insert(FlyingBall);
+            }
+            if let Some(caught) = ball.caught {
+                ball_commands.insert(caught);
+            }
+        }
+        queue.apply(world);
+
+        let mut paddle_query =
+            world.query_filtered::<(&mut Transform, &mut Velocity, &Player), With<Paddle>>();
+        for (mut tf, mut velocity, player) in paddle_query.iter_mut(world) {
+            if let Some(paddle) = self.paddles.iter().find(|paddle| paddle.player == *player) {
+                tf.translation = paddle.translation;
+                velocity.0 = paddle.velocity;
+            }
+        }
+        for (mut score, player) in world.query::<(&mut Score, &Player)>().iter_mut(world) {
+            if let Some(&saved_score) = self.scores.get(player.0) {
+                score.0 = saved_score;
+            }
+        }
+
+        world.insert_resource(Lives(self.lives));
+        // Also resizes the paddle
+        world.insert_resource(Level {
+            number: self.level,
+            name: self.level_name.clone(),
+        });
+        world.insert_resource(ElapsedTime(self.elapsed_time.clone()));
+        world.insert_resource(ActiveEffects(self.effects.clone()));
+        world.insert_resource(BrickRespawn {
+            immediate_spawn: self.brick_immediate_spawn,
+            timer: self.brick_respawn_timer.clone(),
+        });
+        world.insert_resource(GameRng(self.rng.clone()));
+    }
+
+    /// Load the save file, if there is one of the current version.
+    pub fn load() -> Option<SavedGame> {
+        let path = save_path()?;
+        let content = fs::read_to_string(&path).ok()?;
+
+        match ron::from_str::<SaveVersion>(&content) {
+            Ok(SaveVersion { version }) if version == SAVE_VERSION => (),
+            Ok(SaveVersion { version }) => {
+                warn!(
+                    "Ignored save file {} of version {}",
+                    path.display(),
+                    version
+                );
+                return None;
+            }
+            Err(err) => {
+                warn!("Invalid save file {}: {}", path.display(), err);
+                return None;
+            }
+        }
+
+        ron::from_str(&content)
+            .map_err(|err| warn!("Invalid save file {}: {}", path.display(), err))
+            .ok()
+    }
+
+    /// Write the save file, errors are logged.
+    pub fn save(&self) {
+        let path = match save_path() {
+            Some(path) => path,
+            None => {
+                warn!("No configuration directory to save the game");
+                return;
+            }
+        };
+
+        let result = fs::create_dir_all(path.parent().unwrap_or(&path)).and_then(|_| {
+            let content = ron::ser::to_string_pretty(self, Default::default())
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+            fs::write(&path, content)
+        });
+
+        if let Err(err) = result {
+            warn!("Cannot save the game in {}: {}", path.display(), err);
+        }
+    }
+}
+
+fn load_game_system(world: &mut World, mut started: Local<bool>) {
+    let cfg = world.resource::<GameConfig>();
+    let resume = cfg.resume_saved_game && !*started;
+    let key = cfg.key_bindings.load_game;
+    *started = true;
+
+    if resume || world.resource::<Input<KeyCode>>().just_pressed(key) {
+        if let Some(saved_game) = SavedGame::load() {
+            saved_game.restore(world);
+        }
+    }
+}
+
+fn save_game_system(world: &mut World) {
+    let cfg = world.resource::<GameConfig>();
+    let closing = cfg.resume_saved_game && !world.resource::<Events<AppExit>>().is_empty();
+    let key = cfg.key_bindings.save_game;
+
+    if closing || world.resource::<Input<KeyCode>>().just_pressed(key) {
+        SavedGame::capture(world).save();
+    }
+}
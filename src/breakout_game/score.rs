@@ -1,12 +1,17 @@
 //! Score related stuff.
+//!
+//!  - Spawn a score per [`Player`] once at [`StartupStage::PostStartup`] stage.
+//!  - Increase the score of the ball’s player on [`BrickDestroyed`] at [`GameStage::Events`] stage.
+//!  - Increase the score of the opponent on [`BallLost`] and send [`GameOver`] with the winner
+//!    in [`GameMode::Versus`] at [`GameStage::Events`] stage.
+//!  - Update the [`HighScore`] with the reached level and reset the scores on [`GameOver`] at [`GameStage::Events`] stage.
 
 use bevy::prelude::*;
 
-use crate::breakout_game::components::ScoreText;
-
 use super::{
-    components::Score,
-    resources::{GameAssets, GameConfig},
+    components::{Ball, Player, Score},
+    events::{BallLost, BrickDestroyed, GameOver},
+    resources::{GameConfig, GameMode, HighScore, Level},
     GameStage,
 };
 
@@ -15,42 +20,87 @@ pub struct ScorePlugin;
 
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system_to_stage(StartupStage::PostStartup, spawn_score)
-            .add_system_to_stage(GameStage::Ui, update_score_system);
+        app.insert_resource(HighScore::default())
+            .add_startup_system_to_stage(StartupStage::PostStartup, spawn_score)
+            .add_system_to_stage(GameStage::Events, brick_destroyed_score_system)
+            .add_system_to_stage(
+                GameStage::Events,
+                goal_score_system.after(brick_destroyed_score_system),
+            )
+            .add_system_to_stage(
+                GameStage::Events,
+                high_score_system.after(goal_score_system),
+            );
+    }
+}
+
+fn spawn_score(mut commands: Commands, cfg: Res<GameConfig>) {
+    for player in (0..cfg.game_mode.players()).map(Player) {
+        commands.spawn((Score(cfg.startup_score), player));
+    }
+}
+
+fn brick_destroyed_score_system(
+    mut brick_destroyed_events: EventReader<BrickDestroyed>,
+    mut score_query: Query<(&mut Score, &Player)>,
+    ball_query: Query<&Player, With<Ball>>,
+    cfg: Res<GameConfig>,
+) {
+    for event in brick_destroyed_events.iter() {
+        let player = ball_query.get(event.ball).copied().unwrap_or_default();
+        for (mut score, _) in score_query.iter_mut().filter(|(_, &owner)| owner == player) {
+            score.0 += cfg.score_brick;
+        }
     }
 }
 
-fn spawn_score(mut commands: Commands, game_assets: Res<GameAssets>, cfg: Res<GameConfig>) {
-    commands.spawn(Score(cfg.startup_score));
-
-    commands.spawn((
-        // Create a TextBundle that has a Text with a list of sections.
-        TextBundle::from_sections([
-            TextSection::new(
-                "Score: ",
-                TextStyle {
-                    font: game_assets.score_font.clone(),
-                    font_size: cfg.score_font_size,
-                    color: Color::WHITE,
-                },
-            ),
-            TextSection::from_style(TextStyle {
-                font: game_assets.score_font.clone(),
-                font_size: cfg.score_font_size,
-                color: Color::GOLD,
-            }),
-        ]),
-        ScoreText { section: 1 },
-    ));
+fn goal_score_system(
+    mut ball_lost_events: EventReader<BallLost>,
+    mut score_query: Query<(&mut Score, &Player)>,
+    mut game_over_events: EventWriter<GameOver>,
+    cfg: Res<GameConfig>,
+) {
+    if cfg.game_mode != GameMode::Versus {
+        return;
+    }
+
+    for event in ball_lost_events.iter() {
+        for (mut score, &player) in score_query.iter_mut() {
+            if player == event.player {
+                continue;
+            }
+
+            score.0 += cfg.score_goal;
+            if score.0 >= cfg.versus_winning_score {
+                game_over_events.send(GameOver {
+                    winner: Some(player),
+                });
+            }
+        }
+    }
 }
 
-fn update_score_system(
-    score_query: Query<&Score>,
-    mut score_text_query: Query<(&mut Text, &ScoreText)>,
+fn high_score_system(
+    mut game_over_events: EventReader<GameOver>,
+    mut score_query: Query<&mut Score>,
+    mut high_score: ResMut<HighScore>,
+    level: Res<Level>,
+    cfg: Res<GameConfig>,
 ) {
-    if let Ok(score) = score_query.get_single() {
-        for (mut text, score_text) in score_text_query.iter_mut() {
-            text.sections[score_text.section].value = score.0.to_string();
+    // The high score is a solo record
+    if let (GameMode::Solo, Ok(score)) = (cfg.game_mode, score_query.get_single()) {
+        // The level also increases while the high score is being beaten
+        if score.0 > high_score.score
+            || (score.0 == high_score.score && level.number > high_score.level)
+        {
+            high_score.score = score.0;
+            high_score.level = level.number;
+        }
+    }
+
+    if game_over_events.iter().next().is_some() {
+        for mut score in score_query.iter_mut() {
+            score.0 = cfg.startup_score;
         }
     }
 }
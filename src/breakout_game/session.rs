@@ -0,0 +1,93 @@
+//! Game session related stuff, from the first throw to the game over.
+//!
+//!  - Insert [`Lives`], [`ElapsedTime`], [`ActiveEffects`] and [`GameRng`] resources.
+//!  - Lose a life on [`BallLost`] and send [`GameOver`] at [`GameStage::Events`] stage, except in [`GameMode::Versus`].
+//!  - Start a new session on [`GameOver`] at [`GameStage::Events`] stage.
+//!  - Count the elapsed time and remove the ended effects at [`GameStage::Events`] stage.
+
+use super::{
+    events::{BallLost, GameOver},
+    resources::{ActiveEffects, ElapsedTime, GameConfig, GameMode, GameRng, Lives},
+    GameStage,
+};
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::time::Duration;
+
+/// Game session logic as a Bevy’s plugin. (see the game rules)
+pub struct SessionPlugin;
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ElapsedTime::default())
+            .insert_resource(ActiveEffects::default())
+            .add_startup_system(init_lives_system)
+            .add_startup_system(init_rng_system)
+            .add_system_to_stage(GameStage::Events, ball_lost_lives_system)
+            .add_system_to_stage(GameStage::Events, game_over_session_system)
+            .add_system_to_stage(GameStage::Events, elapsed_time_system)
+            .add_system_to_stage(GameStage::Events, active_effects_system);
+    }
+}
+
+fn init_lives_system(mut commands: Commands, cfg: Res<GameConfig>) {
+    commands.insert_resource(Lives(cfg.startup_lives));
+}
+
+fn init_rng_system(mut commands: Commands, cfg: Res<GameConfig>) {
+    let rng = match cfg.seed {
+        Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+        None => ChaCha8Rng::from_entropy(),
+    };
+    commands.insert_resource(GameRng(rng));
+}
+
+fn ball_lost_lives_system(
+    mut ball_lost_events: EventReader<BallLost>,
+    mut game_over_events: EventWriter<GameOver>,
+    mut lives: ResMut<Lives>,
+    cfg: Res<GameConfig>,
+) {
+    for _ in ball_lost_events.iter() {
+        // Players score goals instead of losing lives
+        if cfg.game_mode == GameMode::Versus {
+            continue;
+        }
+
+        if lives.0 > 0 {
+            lives.0 -= 1;
+            if lives.0 == 0 {
+                game_over_events.send(GameOver { winner: None });
+            }
+        }
+    }
+}
+
+fn game_over_session_system(
+    mut game_over_events: EventReader<GameOver>,
+    mut lives: ResMut<Lives>,
+    mut elapsed_time: ResMut<ElapsedTime>,
+    mut effects: ResMut<ActiveEffects>,
+    cfg: Res<GameConfig>,
+) {
+    if game_over_events.iter().next().is_some() {
+        lives.0 = cfg.startup_lives;
+        elapsed_time.0.reset();
+        effects.0.clear();
+    }
+}
+
+fn elapsed_time_system(mut elapsed_time: ResMut<ElapsedTime>, cfg: Res<GameConfig>) {
+    elapsed_time
+        .0
+        .tick(Duration::from_secs_f32(cfg.time_step()));
+}
+
+fn active_effects_system(mut effects: ResMut<ActiveEffects>, cfg: Res<GameConfig>) {
+    let step = Duration::from_secs_f32(cfg.time_step());
+    for effect in effects.0.iter_mut() {
+        effect.timer.tick(step);
+    }
+    effects.0.retain(|effect| !effect.timer.finished());
+}
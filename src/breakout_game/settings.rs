@@ -0,0 +1,401 @@
+//! Settings menu related stuff.
+//!
+//! The menu is opened with Escape or the Start button of a gamepad, the game is paused while it is open.
+//! It is navigated with the arrow keys or the D-pad, Enter or the South button toggles or rebinds.
+//! Preferences are saved with [`UserPreferences`] when the menu is closed.
+//! The settings changing the simulation can’t be changed during a network game, see [`NetSession`].
+//!
+//!  - Process menu input at [`GameStage::Ui`] stage.
+//!  - (Re)spawn the menu when it changes at [`GameStage::Ui`] stage.
+//!  - Apply the window mode at [`GameStage::Ui`] stage.
+
+use super::{
+    components::SettingsMenuRoot,
+    net::NetSession,
+    preferences::UserPreferences,
+    resources::{
+        ActiveTheme, GameAssets, GameConfig, LaunchMode, Localization, Paused, SettingsMenu, Themes,
+    },
+    theme::Theme,
+    GameStage,
+};
+use bevy::{prelude::*, window::WindowMode};
+
+/// Framerates the player can choose from.
+const FRAMERATES: [f32; 4] = [30., 60., 120., 144.];
+
+/// Settings menu logic as a Bevy’s plugin.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SettingsMenu::default())
+            .add_system_to_stage(GameStage::Ui, settings_input_system)
+            .add_system_to_stage(
+                GameStage::Ui,
+                spawn_settings_menu_system.after(settings_input_system),
+            )
+            .add_system_to_stage(GameStage::Ui, window_mode_system);
+    }
+}
+
+/// Items of the settings menu, in display order.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SettingsItem {
+    Language,
+    Volume,
+    Mute,
+    KeyLeft,
+    KeyRight,
+    KeyLaunch,
+    KeyAimLeft,
+    KeyAimRight,
+    LaunchMode,
+    PaddleCatch,
+    Sensitivity,
+    Theme,
+    ColorBlindPalette,
+    BrickGlyphs,
+    HighContrast,
+    UiScale,
+    ReducedMotion,
+    Framerate,
+    Fullscreen,
+}
+
+impl SettingsItem {
+    const ALL: [SettingsItem; 19] = [
+        SettingsItem::Language,
+        SettingsItem::Volume,
+        SettingsItem::Mute,
+        SettingsItem::KeyLeft,
+        SettingsItem::KeyRight,
+        SettingsItem::KeyLaunch,
+        SettingsItem::KeyAimLeft,
+        SettingsItem::KeyAimRight,
+        SettingsItem::LaunchMode,
+        SettingsItem::PaddleCatch,
+        SettingsItem::Sensitivity,
+        SettingsItem::Theme,
+        SettingsItem::ColorBlindPalette,
+        SettingsItem::BrickGlyphs,
+        SettingsItem::HighContrast,
+        SettingsItem::UiScale,
+        SettingsItem::ReducedMotion,
+        SettingsItem::Framerate,
+        SettingsItem::Fullscreen,
+    ];
+
+    /// Whether the setting changes the simulation, which both peers of a network game must share.
+    fn changes_simulation(self) -> bool {
+        matches!(
+            self,
+            SettingsItem::LaunchMode | SettingsItem::PaddleCatch | SettingsItem::Sensitivity
+        )
+    }
+
+    /// Text displayed for this item.
+    fn label(self, cfg: &GameConfig, theme_name: &str, localization: &Localization) -> String {
+        let on_off =
+            |value: bool| localization.text(if value { "settings-on" } else { "settings-off" });
+
+        let (key, value) = match self {
+            SettingsItem::Language => ("settings-language", localization.catalog().name.clone()),
+            SettingsItem::Volume => ("settings-volume", format!("{:.0}", cfg.audio_volume * 100.)),
+            SettingsItem::Mute => ("settings-mute", on_off(cfg.audio_muted)),
+            SettingsItem::KeyLeft => ("settings-key-left", format!("{:?}", cfg.key_bindings.left)),
+            SettingsItem::KeyRight => (
+                "settings-key-right",
+                format!("{:?}", cfg.key_bindings.right),
+            ),
+            SettingsItem::KeyLaunch => (
+                "settings-key-launch",
+                format!("{:?}", cfg.key_bindings.launch),
+            ),
+            SettingsItem::KeyAimLeft => (
+                "settings-key-aim-left",
+                format!("{:?}", cfg.key_bindings.aim_left),
+            ),
+            SettingsItem::KeyAimRight => (
+                "settings-key-aim-right",
+                format!("{:?}", cfg.key_bindings.aim_right),
+            ),
+            SettingsItem::LaunchMode => (
+                "settings-launch-mode",
+                localization.text(match cfg.ball_launch_mode {
+                    LaunchMode::Random => "settings-launch-random",
+                    LaunchMode::Aimed => "settings-launch-aimed",
+                    LaunchMode::PaddleMotion => "settings-launch-paddle-motion",
+                }),
+            ),
+            SettingsItem::PaddleCatch => ("settings-paddle-catch", on_off(cfg.paddle_catch)),
+            SettingsItem::Sensitivity => (
+                "settings-sensitivity",
+                format!("{:.1}", cfg.paddle_sensitivity),
+            ),
+            SettingsItem::Theme => ("settings-theme", theme_name.to_string()),
+            SettingsItem::ColorBlindPalette => (
+                "settings-color-blind-palette",
+                on_off(cfg.color_blind_palette),
+            ),
+            SettingsItem::BrickGlyphs => ("settings-brick-glyphs", on_off(cfg.brick_glyphs)),
+            SettingsItem::HighContrast => ("settings-high-contrast", on_off(cfg.high_contrast)),
+            SettingsItem::UiScale => ("settings-ui-scale", format!("{:.1}", cfg.ui_scale)),
+            SettingsItem::ReducedMotion => ("settings-reduced-motion", on_off(cfg.reduced_motion)),
+            SettingsItem::Framerate => ("settings-framerate", cfg.framerate.to_string()),
+            SettingsItem::Fullscreen => ("settings-fullscreen", on_off(cfg.fullscreen)),
+        };
+
+        localization.format(key, &[("value", &value)])
+    }
+
+    /// Change the setting by one step, `direction` is `-1` or `1`.
+    fn change(
+        self,
+        direction: i32,
+        cfg: &mut GameConfig,
+        themes: &mut Themes,
+        localization: &Localization,
+    ) {
+        let step = direction as f32 * 0.1;
+
+        match self {
+            SettingsItem::Language => {
+                let len = localization.catalogs.len() as i32;
+                let index = (localization.active as i32 + direction).rem_euclid(len);
+                cfg.language = localization.catalogs[index as usize].language.clone();
+            }
+            SettingsItem::Volume => cfg.audio_volume = (cfg.audio_volume + step).clamp(0., 1.),
+            SettingsItem::Mute => cfg.audio_muted = !cfg.audio_muted,
+            SettingsItem::KeyLeft
+            | SettingsItem::KeyRight
+            | SettingsItem::KeyLaunch
+            | SettingsItem::KeyAimLeft
+            | SettingsItem::KeyAimRight => (),
+            SettingsItem::LaunchMode => {
+                const MODES: [LaunchMode; 3] = [
+                    LaunchMode::Random,
+                    LaunchMode::Aimed,
+                    LaunchMode::PaddleMotion,
+                ];
+                let index = MODES
+                    .iter()
+                    .position(|&mode| mode == cfg.ball_launch_mode)
+                    .unwrap_or(0) as i32;
+                let index = (index + direction).rem_euclid(MODES.len() as i32);
+                cfg.ball_launch_mode = MODES[index as usize];
+            }
+            SettingsItem::PaddleCatch => cfg.paddle_catch = !cfg.paddle_catch,
+            SettingsItem::Sensitivity => {
+                cfg.paddle_sensitivity = (cfg.paddle_sensitivity + step).clamp(0.2, 3.)
+            }
+            SettingsItem::Theme => {
+                let len = themes.handles.len() as i32;
+                themes.active = (themes.active as i32 + direction).rem_euclid(len) as usize;
+                cfg.theme = themes.active;
+            }
+            SettingsItem::ColorBlindPalette => cfg.color_blind_palette = !cfg.color_blind_palette,
+            SettingsItem::BrickGlyphs => cfg.brick_glyphs = !cfg.brick_glyphs,
+            SettingsItem::HighContrast => cfg.high_contrast = !cfg.high_contrast,
+            SettingsItem::UiScale => cfg.ui_scale = (cfg.ui_scale + step).clamp(0.5, 2.),
+            SettingsItem::ReducedMotion => cfg.reduced_motion = !cfg.reduced_motion,
+            SettingsItem::Framerate => {
+                let index = FRAMERATES
+                    .iter()
+                    .position(|&framerate| framerate == cfg.framerate)
+                    .unwrap_or(1) as i32;
+                let index = (index + direction).rem_euclid(FRAMERATES.len() as i32);
+                cfg.framerate = FRAMERATES[index as usize];
+            }
+            SettingsItem::Fullscreen => cfg.fullscreen = !cfg.fullscreen,
+        }
+    }
+}
+
+/// Check if a button has just been pressed on any gamepad.
+fn gamepad_just_pressed(
+    gamepads: &Gamepads,
+    buttons: &Input<GamepadButton>,
+    button_type: GamepadButtonType,
+) -> bool {
+    gamepads
+        .iter()
+        .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn settings_input_system(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    mut menu: ResMut<SettingsMenu>,
+    mut paused: ResMut<Paused>,
+    mut themes: ResMut<Themes>,
+    localization: Res<Localization>,
+    session: Option<Res<NetSession>>,
+    mut cfg: ResMut<GameConfig>,
+) {
+    let pressed = |key: KeyCode, button_type: GamepadButtonType| {
+        keys.just_pressed(key) || gamepad_just_pressed(&gamepads, &buttons, button_type)
+    };
+
+    if menu.rebinding {
+        if let Some(&key) = keys.get_just_pressed().next() {
+            let item = SettingsItem::ALL[menu.selected];
+            let bindings = &mut cfg.key_bindings;
+            match item {
+                SettingsItem::KeyLeft => bindings.left = key,
+                SettingsItem::KeyRight => bindings.right = key,
+                SettingsItem::KeyLaunch => bindings.launch = key,
+                SettingsItem::KeyAimLeft => bindings.aim_left = key,
+                SettingsItem::KeyAimRight => bindings.aim_right = key,
+                _ => (),
+            }
+            menu.rebinding = false;
+        }
+        return;
+    }
+
+    if pressed(KeyCode::Escape, GamepadButtonType::Start)
+        || (menu.open && pressed(KeyCode::Back, GamepadButtonType::East))
+    {
+        menu.open = !menu.open;
+        paused.0 = menu.open;
+
+        if !menu.open {
+            let mut preferences = UserPreferences::from_config(&cfg);
+            if session.is_some() {
+                // The simulation settings of the game aren’t the player’s
+                preferences = preferences.with_simulation_of(&UserPreferences::load());
+            }
+            preferences.save();
+        }
+        return;
+    }
+
+    if !menu.open {
+        return;
+    }
+
+    let len = SettingsItem::ALL.len();
+    if pressed(KeyCode::Down, GamepadButtonType::DPadDown) {
+        menu.selected = (menu.selected + 1) % len;
+    } else if pressed(KeyCode::Up, GamepadButtonType::DPadUp) {
+        menu.selected = (menu.selected + len - 1) % len;
+    }
+
+    let item = SettingsItem::ALL[menu.selected];
+    if session.is_some() && item.changes_simulation() {
+        return;
+    }
+    if pressed(KeyCode::Left, GamepadButtonType::DPadLeft) {
+        item.change(-1, &mut cfg, &mut themes, &localization);
+    } else if pressed(KeyCode::Right, GamepadButtonType::DPadRight) {
+        item.change(1, &mut cfg, &mut themes, &localization);
+    } else if pressed(KeyCode::Return, GamepadButtonType::South) {
+        match item {
+            SettingsItem::KeyLeft
+            | SettingsItem::KeyRight
+            | SettingsItem::KeyLaunch
+            | SettingsItem::KeyAimLeft
+            | SettingsItem::KeyAimRight => menu.rebinding = true,
+            _ => item.change(1, &mut cfg, &mut themes, &localization),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_settings_menu_system(
+    mut commands: Commands,
+    menu: Res<SettingsMenu>,
+    root_query: Query<Entity, With<SettingsMenuRoot>>,
+    themes: Res<Themes>,
+    theme_assets: Res<Assets<Theme>>,
+    game_assets: Res<GameAssets>,
+    active_theme: Res<ActiveTheme>,
+    localization: Res<Localization>,
+    cfg: Res<GameConfig>,
+) {
+    if !menu.is_changed()
+        && !cfg.is_changed()
+        && !active_theme.is_changed()
+        && !localization.is_changed()
+        && !game_assets.is_changed()
+    {
+        return;
+    }
+
+    for root in root_query.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+
+    if !menu.open {
+        return;
+    }
+
+    let theme_name = theme_assets
+        .get(&themes.handles[themes.active])
+        .map_or("", |theme| theme.name.as_str());
+    let font_size = cfg.hud_font_size * cfg.ui_scale;
+    let mut background_color = active_theme.0.background;
+    background_color.set_a(0.9);
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: background_color.into(),
+            // Above the HUD
+            z_index: ZIndex::Global(1),
+            ..Default::default()
+        })
+        .insert(SettingsMenuRoot)
+        .with_children(|parent| {
+            for (index, item) in SettingsItem::ALL.iter().enumerate() {
+                let selected = index == menu.selected;
+                let text = match (selected, menu.rebinding) {
+                    (true, true) => localization.text("settings-press-key"),
+                    (true, false) => {
+                        format!("> {} <", item.label(&cfg, theme_name, &localization))
+                    }
+                    (false, _) => item.label(&cfg, theme_name, &localization),
+                };
+
+                parent.spawn(TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font: game_assets.score_font.clone(),
+                        font_size,
+                        color: if selected {
+                            active_theme.0.hud_value
+                        } else {
+                            active_theme.0.hud_label
+                        },
+                    },
+                ));
+            }
+        });
+}
+
+fn window_mode_system(mut windows: ResMut<Windows>, cfg: Res<GameConfig>) {
+    if !cfg.is_changed() {
+        return;
+    }
+
+    if let Some(window) = windows.get_primary_mut() {
+        let mode = if cfg.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+        if window.mode() != mode {
+            window.set_mode(mode);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Camera shake related stuff.
+//!
+//!  - Insert a [`CameraShake`] resource.
+//!  - Add trauma on [`BrickDestroyed`] at [`GameStage::Events`] stage, unless reduced motion is enabled.
+//!  - Shake the camera according to the trauma at [`GameStage::Ui`] stage.
+
+use super::{
+    components::BrickKind,
+    events::BrickDestroyed,
+    resources::{CameraShake, GameConfig},
+    GameStage,
+};
+use bevy::prelude::*;
+use rand::{thread_rng, Rng};
+
+/// Camera shake logic as a Bevy’s plugin.
+///
+/// Need a [`bevy::prelude::Camera2dBundle`] to be spawned to works.
+pub struct ShakePlugin;
+
+impl Plugin for ShakePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CameraShake::default())
+            .add_system_to_stage(GameStage::Events, brick_destroyed_shake_system)
+            .add_system_to_stage(GameStage::Ui, camera_shake_system);
+    }
+}
+
+/// Trauma added by destroying a brick of the given kind.
+fn brick_trauma(kind: BrickKind, cfg: &GameConfig) -> f32 {
+    match kind {
+        BrickKind::Normal => cfg.camera_shake_brick,
+        BrickKind::Strong => cfg.camera_shake_brick * 1.5,
+    }
+}
+
+fn brick_destroyed_shake_system(
+    mut brick_destroyed_events: EventReader<BrickDestroyed>,
+    mut shake: ResMut<CameraShake>,
+    cfg: Res<GameConfig>,
+) {
+    if cfg.reduced_motion {
+        brick_destroyed_events.clear();
+        shake.trauma = 0.;
+        return;
+    }
+
+    for event in brick_destroyed_events.iter() {
+        shake.trauma = (shake.trauma + brick_trauma(event.kind, &cfg)).min(1.);
+    }
+}
+
+fn camera_shake_system(
+    time: Res<Time>,
+    mut shake: ResMut<CameraShake>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    cfg: Res<GameConfig>,
+) {
+    let mut rng = thread_rng();
+
+    // Squared trauma gives a smoother falloff
+    let intensity = shake.trauma * shake.trauma * cfg.camera_shake_max_offset;
+    let offset = Vec2::new(
+        rng.gen_range(-1. ..=1.) * intensity,
+        rng.gen_range(-1. ..=1.) * intensity,
+    );
+
+    for mut tf in camera_query.iter_mut() {
+        tf.translation.x = offset.x;
+        tf.translation.y = offset.y;
+    }
+
+    shake.trauma = (shake.trauma - cfg.camera_shake_decay * time.delta_seconds()).max(0.);
+}
@@ -0,0 +1,244 @@
+//! Skin related stuff.
+//!
+//! A skin replaces the vector shapes of the game by regions of a texture atlas.
+//! It is described by a [`SkinManifest`] loaded from [`GameConfig::skin_path`], for example:
+//!
+//! ```ron
+//! (
+//!     texture: "skins/wood.png",
+//!     size: (256, 128),
+//!     paddle: Some((x: 0, y: 0, width: 80, height: 15)),
+//!     ball: Some((x: 80, y: 0, width: 14, height: 14)),
+//!     wall: None,
+//!     bricks: [
+//!         (x: 0, y: 20, width: 40, height: 20),
+//!         (x: 40, y: 20, width: 40, height: 20),
+//!     ],
+//! )
+//! ```
+//!
+//! Entities without a region, or all of them while the texture is missing, keep their vector shape.
+//! Visuals are stretched to the [`BoundingBox`], so the gameplay is the same with or without a skin.
+//!
+//!  - Load the skin at [`StartupStage::Startup`] stage.
+//!  - Replace the vector shapes by sprites at [`GameStage::Ui`] stage.
+
+use super::{
+    components::{Ball, BoundingBox, Brick, BrickCell, Paddle, Wall},
+    resources::{GameConfig, Skin},
+    GameStage,
+};
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, Error, LoadContext, LoadedAsset},
+    math::Rect,
+    prelude::*,
+    reflect::TypeUuid,
+    sprite::Mesh2dHandle,
+};
+use serde::{Deserialize, Serialize};
+
+/// Skin logic as a Bevy’s plugin.
+pub struct SkinPlugin;
+
+impl Plugin for SkinPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<SkinManifest>()
+            .init_asset_loader::<SkinLoader>()
+            .add_startup_system(load_skin_system)
+            .add_system_to_stage(GameStage::Ui, build_skin_atlas_system)
+            .add_system_to_stage(GameStage::Ui, apply_skin_system);
+    }
+}
+
+/// A rectangular region of the skin’s texture, in pixels from the top left corner.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Region {
+    /// Left of the region.
+    pub x: u32,
+    /// Top of the region.
+    pub y: u32,
+    /// Width of the region.
+    pub width: u32,
+    /// Height of the region.
+    pub height: u32,
+}
+
+impl From<Region> for Rect {
+    fn from(region: Region) -> Rect {
+        Rect::new(
+            region.x as f32,
+            region.y as f32,
+            (region.x + region.width) as f32,
+            (region.y + region.height) as f32,
+        )
+    }
+}
+
+/// Description of a skin, which regions of the texture are used for which entities.
+#[derive(Serialize, Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "5d0e4c3a-9f6b-4a77-b2a9-3c1d7f0e8b64"]
+pub struct SkinManifest {
+    /// Path to the texture, relative to the assets folder.
+    pub texture: String,
+    /// Texture’s size in pixels.
+    pub size: (u32, u32),
+    /// Paddle’s region.
+    pub paddle: Option<Region>,
+    /// Ball’s region.
+    pub ball: Option<Region>,
+    /// Wall’s region.
+    pub wall: Option<Region>,
+    /// Brick’s regions by row, starting from the top row and repeated if there are more rows than regions.
+    pub bricks: Vec<Region>,
+}
+
+/// Atlas indices of the regions of a [`SkinManifest`].
+#[derive(Default)]
+pub struct SkinIndices {
+    /// Paddle’s index.
+    pub paddle: Option<usize>,
+    /// Ball’s index.
+    pub ball: Option<usize>,
+    /// Wall’s index.
+    pub wall: Option<usize>,
+    /// Brick’s indices by row, starting from the top row.
+    pub bricks: Vec<usize>,
+}
+
+/// Load [`SkinManifest`] from files with `.skin.ron` extension.
+#[derive(Default)]
+pub struct SkinLoader;
+
+impl AssetLoader for SkinLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let manifest: SkinManifest = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(manifest));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["skin.ron"]
+    }
+}
+
+fn load_skin_system(mut commands: Commands, asset_server: Res<AssetServer>, cfg: Res<GameConfig>) {
+    if let Some(path) = &cfg.skin_path {
+        commands.insert_resource(Skin {
+            manifest: asset_server.load(path),
+            texture: None,
+            atlas: None,
+            indices: SkinIndices::default(),
+        });
+    }
+}
+
+fn build_skin_atlas_system(
+    skin: Option<ResMut<Skin>>,
+    manifests: Res<Assets<SkinManifest>>,
+    images: Res<Assets<Image>>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+    asset_server: Res<AssetServer>,
+) {
+    let mut skin = match skin {
+        Some(skin) if skin.atlas.is_none() => skin,
+        _ => return,
+    };
+    let manifest = match manifests.get(&skin.manifest) {
+        Some(manifest) => manifest,
+        None => return,
+    };
+
+    let texture = match &skin.texture {
+        Some(texture) => texture.clone(),
+        None => {
+            let texture = asset_server.load(&manifest.texture);
+            skin.texture = Some(texture.clone());
+            texture
+        }
+    };
+
+    // Keep the vector shapes until the texture is loaded, forever if it is missing
+    if !images.contains(&texture) {
+        return;
+    }
+
+    let mut atlas = TextureAtlas::new_empty(
+        texture,
+        Vec2::new(manifest.size.0 as f32, manifest.size.1 as f32),
+    );
+    let mut add = |region: Region| atlas.add_texture(region.into());
+    let indices = SkinIndices {
+        paddle: manifest.paddle.map(&mut add),
+        ball: manifest.ball.map(&mut add),
+        wall: manifest.wall.map(&mut add),
+        bricks: manifest.bricks.iter().copied().map(&mut add).collect(),
+    };
+
+    skin.indices = indices;
+    skin.atlas = Some(atlases.add(atlas));
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_skin_system(
+    mut commands: Commands,
+    skin: Option<Res<Skin>>,
+    query: Query<
+        (
+            Entity,
+            &Transform,
+            &BoundingBox,
+            Option<&BrickCell>,
+            Option<&Paddle>,
+            Option<&Ball>,
+            Option<&Brick>,
+            Option<&Wall>,
+        ),
+        (With<Mesh2dHandle>, Without<TextureAtlasSprite>),
+    >,
+    cfg: Res<GameConfig>,
+) {
+    let (skin, atlas) = match &skin {
+        Some(skin) => match &skin.atlas {
+            Some(atlas) => (skin, atlas),
+            None => return,
+        },
+        None => return,
+    };
+    let indices = &skin.indices;
+
+    for (entity, tf, bb, cell, paddle, ball, brick, wall) in query.iter() {
+        let index = if paddle.is_some() {
+            indices.paddle
+        } else if ball.is_some() {
+            indices.ball
+        } else if wall.is_some() {
+            indices.wall
+        } else if brick.is_some() && !indices.bricks.is_empty() {
+            let row = cell.map_or(0, |cell| cell.row);
+            let row_from_top = cfg.brick_area_rows.saturating_sub(row + 1) as usize;
+            Some(indices.bricks[row_from_top % indices.bricks.len()])
+        } else {
+            None
+        };
+
+        if let Some(index) = index {
+            // Without mesh, the vector shape is not rendered anymore
+            commands
+                .entity(entity)
+                .remove::<Mesh2dHandle>()
+                .insert(TextureAtlasSprite {
+                    index,
+                    // The bounding box already includes the scale (of the paddle, etc.)
+                    custom_size: Some(bb.0 / tf.scale.truncate()),
+                    ..Default::default()
+                })
+                .insert(atlas.clone());
+        }
+    }
+}
@@ -0,0 +1,431 @@
+//! Telemetry stream of the gameplay state, for spectators, dashboards and external bots.
+//!
+//! Clients connect over TCP or a Unix socket and receive a JSON [`Message`] per line:
+//! the gameplay events of the tick then a [`Snapshot`], see the `breakout_telemetry` client crate.
+//! When [`TelemetryConfig::control`] is true, clients can send a [`Control`] per line to move the paddles.
+//!
+//!  - Bind the [`TelemetryServer`] of [`GameConfig::telemetry`], if any, at startup.
+//!  - Replace the [`PlayerInputs`] of the controlled players at [`CoreStage::PreUpdate`] stage,
+//!    except in network play.
+//!  - Forget the played launches of the clients at [`GameStage::Input`] stage.
+//!  - Accept the new clients and publish the events and the snapshot of the tick at [`CoreStage::Last`] stage.
+//!
+//! [`TelemetryConfig::control`]: super::resources::TelemetryConfig::control
+
+use super::{
+    components::{Ball, Brick, BrickCell, BrickHitPoints, Paddle, Player, Score, Velocity},
+    controls::keyboard_input_system,
+    events::{BallLost, BrickDestroyed},
+    net::NetSession,
+    resources::{GameConfig, Paused, PlayerInput, PlayerInputs, Stalled},
+    GameStage,
+};
+use bevy::prelude::*;
+use breakout_telemetry::{BodySnapshot, BrickSnapshot, Control, Endpoint, Message, Snapshot};
+use std::{
+    collections::BTreeMap,
+    io::{self, ErrorKind, Read, Write},
+    net::TcpListener,
+};
+
+/// Maximum size of the data not sent yet to a client, slower clients are disconnected.
+const MAX_BACKLOG: usize = 1 << 20;
+
+/// Telemetry logic as a Bevy’s plugin, it does nothing without a [`TelemetryServer`].
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(init_telemetry_system)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                control_system.after(keyboard_input_system),
+            )
+            .add_system_to_stage(GameStage::Input, played_launch_system)
+            .add_system_to_stage(CoreStage::Last, publish_system);
+    }
+}
+
+/// Any connection to a client.
+trait Stream: Read + Write + Send + Sync {}
+
+impl<T: Read + Write + Send + Sync> Stream for T {}
+
+/// Listening socket of the server.
+enum Listener {
+    Tcp(TcpListener),
+    /// Listener at a path, with the device and inode of its socket file.
+    #[cfg(unix)]
+    Unix(
+        std::os::unix::net::UnixListener,
+        std::path::PathBuf,
+        (u64, u64),
+    ),
+}
+
+impl Listener {
+    /// Listen at `endpoint`, a stale Unix socket is replaced but any other file is [`ErrorKind::AddrInUse`].
+    fn bind(endpoint: &Endpoint) -> io::Result<Listener> {
+        let listener = match endpoint {
+            Endpoint::Tcp(address) => Listener::Tcp(TcpListener::bind(address)?),
+            #[cfg(unix)]
+            Endpoint::Unix(path) => {
+                use std::os::unix::{
+                    fs::{FileTypeExt, MetadataExt},
+                    net::{UnixListener, UnixStream},
+                };
+
+                if let Ok(metadata) = path.symlink_metadata() {
+                    // Stale when nobody listens on it anymore
+                    let stale =
+                        metadata.file_type().is_socket() && UnixStream::connect(path).is_err();
+                    if !stale {
+                        return Err(io::Error::new(
+                            ErrorKind::AddrInUse,
+                            format!("{} is in use", path.display()),
+                        ));
+                    }
+                    std::fs::remove_file(path)?;
+                }
+                let listener = UnixListener::bind(path)?;
+                let metadata = path.symlink_metadata()?;
+                Listener::Unix(listener, path.clone(), (metadata.dev(), metadata.ino()))
+            }
+            #[cfg(not(unix))]
+            Endpoint::Unix(_) => {
+                return Err(io::Error::new(
+                    ErrorKind::Unsupported,
+                    "Unix sockets are not supported",
+                ))
+            }
+        };
+
+        match &listener {
+            Listener::Tcp(listener) => listener.set_nonblocking(true)?,
+            #[cfg(unix)]
+            Listener::Unix(listener, ..) => listener.set_nonblocking(true)?,
+        }
+        Ok(listener)
+    }
+
+    /// The next pending client, if any.
+    fn accept(&self) -> io::Result<Option<Box<dyn Stream>>> {
+        let stream: io::Result<Box<dyn Stream>> = match self {
+            Listener::Tcp(listener) => listener.accept().and_then(|(stream, _)| {
+                stream.set_nonblocking(true)?;
+                stream.set_nodelay(true)?;
+                Ok(Box::new(stream) as Box<dyn Stream>)
+            }),
+            #[cfg(unix)]
+            Listener::Unix(listener, ..) => listener.accept().and_then(|(stream, _)| {
+                stream.set_nonblocking(true)?;
+                Ok(Box::new(stream) as Box<dyn Stream>)
+            }),
+        };
+
+        match stream {
+            Ok(stream) => Ok(Some(stream)),
+            Err(err) if err.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Listener {
+    fn drop(&mut self) {
+        use std::os::unix::fs::MetadataExt;
+
+        if let Listener::Unix(_, path, file) = self {
+            // The file may have been replaced since, by another server
+            let own_file = path
+                .symlink_metadata()
+                .map_or(false, |metadata| (metadata.dev(), metadata.ino()) == *file);
+            if own_file {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// A connected client.
+struct Connection {
+    /// Non-blocking connection.
+    stream: Box<dyn Stream>,
+    /// Data not sent yet.
+    outgoing: Vec<u8>,
+    /// Data received, up to an incomplete line.
+    incoming: Vec<u8>,
+    /// Inputs of the players controlled by the client.
+    controls: BTreeMap<usize, PlayerInput>,
+}
+
+impl Connection {
+    /// Send as much as possible of the outgoing data.
+    fn flush(&mut self) -> io::Result<()> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(len) => {
+                    self.outgoing.drain(..len);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        if self.outgoing.len() > MAX_BACKLOG {
+            return Err(io::Error::new(ErrorKind::Other, "client too slow"));
+        }
+        Ok(())
+    }
+
+    /// Read the controls received since the last call.
+    fn receive(&mut self) -> io::Result<()> {
+        let mut buffer = [0; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(len) => self.incoming.extend_from_slice(&buffer[..len]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        while let Some(end) = self.incoming.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.incoming.drain(..=end).collect();
+            let control: Control = match serde_json::from_slice(&line) {
+                Ok(control) => control,
+                Err(err) => {
+                    warn!("Invalid control: {}", err);
+                    continue;
+                }
+            };
+            let input = self.controls.entry(control.player).or_default();
+            *input = PlayerInput {
+                left: control.left,
+                right: control.right,
+                // Kept until it is played
+                launch: input.launch || control.launch,
+                aim_left: control.aim_left,
+                aim_right: control.aim_right,
+            };
+        }
+
+        if self.incoming.len() > MAX_BACKLOG {
+            return Err(io::Error::new(ErrorKind::InvalidData, "line too long"));
+        }
+        Ok(())
+    }
+}
+
+/// Server publishing the gameplay state to its clients.
+#[derive(Resource)]
+pub struct TelemetryServer {
+    /// Listening socket.
+    listener: Listener,
+    /// Connected clients.
+    clients: Vec<Connection>,
+    /// If true, the clients can move the paddles.
+    control: bool,
+    /// Number of ticks since the server started.
+    tick: u64,
+}
+
+impl TelemetryServer {
+    /// Listen at `address`, see [`Endpoint::parse`]; the clients move the paddles if `control` is true.
+    pub fn bind(address: &str, control: bool) -> io::Result<TelemetryServer> {
+        Ok(TelemetryServer {
+            listener: Listener::bind(&Endpoint::parse(address))?,
+            clients: Vec::new(),
+            control,
+            tick: 0,
+        })
+    }
+
+    /// Address of the TCP socket, if it is one.
+    pub fn local_addr(&self) -> Option<std::net::SocketAddr> {
+        match &self.listener {
+            Listener::Tcp(listener) => listener.local_addr().ok(),
+            #[cfg(unix)]
+            Listener::Unix(..) => None,
+        }
+    }
+
+    /// Number of connected clients.
+    pub fn clients(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Accept the pending clients.
+    fn accept(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok(Some(stream)) => self.clients.push(Connection {
+                    stream,
+                    outgoing: Vec::new(),
+                    incoming: Vec::new(),
+                    controls: BTreeMap::new(),
+                }),
+                Ok(None) => break,
+                Err(err) => {
+                    warn!("Cannot accept a telemetry client: {}", err);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Send `messages` to all the clients, the disconnected ones are dropped.
+    fn publish(&mut self, messages: &[Message]) {
+        let mut lines = Vec::new();
+        for message in messages {
+            match serde_json::to_writer(&mut lines, message) {
+                Ok(()) => lines.push(b'\n'),
+                Err(err) => warn!("Cannot serialize a telemetry message: {}", err),
+            }
+        }
+
+        self.clients.retain_mut(|client| {
+            client.outgoing.extend_from_slice(&lines);
+            client.flush().is_ok()
+        });
+    }
+}
+
+fn init_telemetry_system(
+    mut commands: Commands,
+    server: Option<Res<TelemetryServer>>,
+    cfg: Res<GameConfig>,
+) {
+    let telemetry = match (&server, &cfg.telemetry) {
+        (None, Some(telemetry)) => telemetry,
+        _ => return,
+    };
+
+    match TelemetryServer::bind(&telemetry.address, telemetry.control) {
+        Ok(server) => commands.insert_resource(server),
+        Err(err) => error!("Cannot bind {}: {}", telemetry.address, err),
+    }
+}
+
+fn control_system(
+    server: Option<ResMut<TelemetryServer>>,
+    session: Option<Res<NetSession>>,
+    mut inputs: ResMut<PlayerInputs>,
+) {
+    let mut server = match server {
+        Some(server) => server,
+        None => return,
+    };
+    // Only the inputs exchanged with the peer are simulated in network play
+    if !server.control || session.is_some() {
+        return;
+    }
+
+    server.clients.retain_mut(|client| client.receive().is_ok());
+    for client in server.clients.iter_mut() {
+        for (&player, input) in client.controls.iter_mut() {
+            if let Some(player_input) = inputs.0.get_mut(player) {
+                *player_input = *input;
+            }
+        }
+    }
+}
+
+/// Forget the launches of the clients once played, they are kept while the gameplay doesn’t run.
+fn played_launch_system(server: Option<ResMut<TelemetryServer>>) {
+    if let Some(mut server) = server {
+        for client in server.clients.iter_mut() {
+            for input in client.controls.values_mut() {
+                input.launch = false;
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn publish_system(
+    server: Option<ResMut<TelemetryServer>>,
+    mut brick_destroyed_events: EventReader<BrickDestroyed>,
+    mut ball_lost_events: EventReader<BallLost>,
+    paddle_query: Query<(&Transform, &Velocity, &Player), With<Paddle>>,
+    ball_query: Query<(&Transform, &Velocity, &Player), With<Ball>>,
+    brick_query: Query<(&Transform, &BrickCell, &BrickHitPoints), With<Brick>>,
+    score_query: Query<(&Score, &Player)>,
+    changed_score_query: Query<(&Score, &Player), Changed<Score>>,
+    paused: Res<Paused>,
+    stalled: Res<Stalled>,
+) {
+    let mut server = match server {
+        Some(server) => server,
+        None => return,
+    };
+
+    let mut messages: Vec<Message> = brick_destroyed_events
+        .iter()
+        .map(|event| Message::BrickDestroyed {
+            position: event.position.to_array(),
+            player: ball_query
+                .get(event.ball)
+                .map_or(0, |(_, _, player)| player.0),
+        })
+        .collect();
+    messages.extend(ball_lost_events.iter().map(|event| Message::BallLost {
+        player: event.player.0,
+        position: event.position.to_array(),
+    }));
+    messages.extend(
+        changed_score_query
+            .iter()
+            .map(|(score, player)| Message::ScoreChanged {
+                player: player.0,
+                score: score.0,
+            }),
+    );
+
+    let tick = server.tick;
+    server.tick += 1;
+    server.accept();
+    if server.clients.is_empty() {
+        return;
+    }
+
+    let mut paddles: Vec<_> = paddle_query.iter().map(body_snapshot).collect();
+    paddles.sort_by_key(|paddle| paddle.player);
+    let mut scores = Vec::new();
+    for (score, player) in score_query.iter() {
+        if scores.len() <= player.0 {
+            scores.resize(player.0 + 1, 0);
+        }
+        scores[player.0] = score.0;
+    }
+
+    messages.push(Message::Snapshot(Snapshot {
+        tick,
+        paused: paused.0 || stalled.0,
+        paddles,
+        balls: ball_query.iter().map(body_snapshot).collect(),
+        bricks: brick_query
+            .iter()
+            .map(|(tf, cell, hit_points)| BrickSnapshot {
+                col: cell.col,
+                row: cell.row,
+                position: tf.translation.truncate().to_array(),
+                hit_points: hit_points.0,
+            })
+            .collect(),
+        scores,
+    }));
+
+    server.publish(&messages);
+}
+
+/// Snapshot of a paddle or a ball.
+fn body_snapshot((tf, velocity, player): (&Transform, &Velocity, &Player)) -> BodySnapshot {
+    BodySnapshot {
+        player: player.0,
+        position: tf.translation.truncate().to_array(),
+        velocity: velocity.0.to_array(),
+    }
+}
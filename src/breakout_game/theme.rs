@@ -0,0 +1,298 @@
+//! Theme related stuff.
+//!
+//! A [`Theme`] defines all the colors of the game, it is loaded from a RON file.
+//! Built-in themes are embedded in the binary, other themes are loaded from [`GameConfig::theme_paths`].
+//!
+//!  - Insert [`ActiveTheme`] and [`Themes`] resources.
+//!  - Switch to the next theme on key press at [`GameStage::Input`] stage.
+//!  - Update [`ActiveTheme`] with the accessibility options at [`GameStage::Input`] stage.
+//!  - Recolor every entity when the active theme changes at [`GameStage::Ui`] stage.
+//!    The HUD is respawned by [`super::hud`] instead.
+
+use super::{
+    components::{Ball, Brick, BrickCell, BrickColor, BrickHitPoints, BrickKind, Paddle, Wall},
+    resources::{ActiveTheme, CustomLevel, GameConfig, Themes},
+    GameStage,
+};
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, Error, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use bevy_prototype_lyon::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+/// Brick’s colors safe for all kinds of color vision deficiency (Okabe-Ito palette).
+const COLOR_BLIND_SAFE_COLORS: [Color; 7] = [
+    Color::rgb(0.9, 0.62, 0.),
+    Color::rgb(0.34, 0.71, 0.91),
+    Color::rgb(0., 0.62, 0.45),
+    Color::rgb(0.94, 0.89, 0.26),
+    Color::rgb(0., 0.45, 0.7),
+    Color::rgb(0.84, 0.37, 0.),
+    Color::rgb(0.8, 0.47, 0.65),
+];
+
+/// Built-in themes, the first one is the default theme.
+const BUILT_IN_THEMES: [&str; 3] = [
+    include_str!("../../assets/themes/classic.theme.ron"),
+    include_str!("../../assets/themes/neon.theme.ron"),
+    include_str!("../../assets/themes/monochrome.theme.ron"),
+];
+
+/// Theme logic as a Bevy’s plugin.
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Theme>()
+            .init_asset_loader::<ThemeLoader>()
+            .insert_resource(ActiveTheme(Theme::built_in(0)))
+            .add_startup_system(load_themes_system)
+            .add_system_to_stage(GameStage::Input, switch_theme_system)
+            .add_system_to_stage(
+                GameStage::Input,
+                update_active_theme_system.after(switch_theme_system),
+            )
+            .add_system_to_stage(GameStage::Ui, apply_theme_system);
+    }
+}
+
+/// How bricks are colored.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum BrickPalette {
+    /// Colors chosen at random.
+    Random(Vec<Color>),
+    /// Colors by row, starting from the top row and repeated if there are more rows than colors.
+    Rows(Vec<Color>),
+}
+
+/// All the colors of the game.
+#[derive(Serialize, Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "c2a8bd02-5f3f-4c0e-8e4b-1f3d0e6f8a51"]
+pub struct Theme {
+    /// Name displayed to the player.
+    pub name: String,
+    /// Window’s background color.
+    pub background: Color,
+    /// Outline color of paddles, balls and bricks.
+    pub outline: Color,
+    /// Outline width of paddles, balls and bricks in pixels.
+    pub outline_width: f32,
+    /// Paddle’s color.
+    pub paddle: Color,
+    /// Ball’s color.
+    pub ball: Color,
+    /// Wall’s color.
+    pub wall: Color,
+    /// Color of the HUD labels.
+    pub hud_label: Color,
+    /// Color of the HUD values.
+    pub hud_value: Color,
+    /// Trail’s color of a slow ball.
+    pub trail_slow: Color,
+    /// Trail’s color of a ball at [`GameConfig::trail_fast_speed`].
+    pub trail_fast: Color,
+    /// Brick’s colors.
+    pub bricks: BrickPalette,
+    /// Color of the strong bricks, following [`Theme::bricks`] if None.
+    #[serde(default)]
+    pub strong_brick: Option<Color>,
+}
+
+impl Theme {
+    /// Parse the built-in theme at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn built_in(index: usize) -> Theme {
+        ron::from_str(BUILT_IN_THEMES[index]).expect("Built-in themes should be valid")
+    }
+
+    /// Copy of the theme modified according to the accessibility options of `cfg`.
+    pub fn accessible(&self, cfg: &GameConfig) -> Theme {
+        let mut theme = self.clone();
+
+        if cfg.color_blind_palette {
+            theme.bricks = match theme.bricks {
+                BrickPalette::Random(_) => BrickPalette::Random(COLOR_BLIND_SAFE_COLORS.to_vec()),
+                BrickPalette::Rows(_) => BrickPalette::Rows(COLOR_BLIND_SAFE_COLORS.to_vec()),
+            };
+        }
+
+        if cfg.high_contrast {
+            theme.background = Color::BLACK;
+            theme.outline = Color::WHITE;
+            theme.outline_width = theme.outline_width.max(2.);
+            theme.wall = Color::WHITE;
+            theme.paddle = Color::WHITE;
+            theme.ball = Color::YELLOW;
+            theme.hud_label = Color::WHITE;
+            theme.hud_value = Color::YELLOW;
+        }
+
+        theme
+    }
+
+    /// All the colors of the brick’s palette.
+    pub fn brick_colors(&self) -> &[Color] {
+        match &self.bricks {
+            BrickPalette::Random(colors) | BrickPalette::Rows(colors) => colors,
+        }
+    }
+
+    /// Draw mode of an outlined shape filled with `color`.
+    pub fn outlined(&self, color: Color) -> DrawMode {
+        DrawMode::Outlined {
+            fill_mode: FillMode::color(color),
+            outline_mode: StrokeMode::new(self.outline, self.outline_width),
+        }
+    }
+
+    /// Draw mode of a brick filled with `color`, the outline is thicker with more `hit_points`.
+    pub fn outlined_brick(&self, color: Color, hit_points: u32) -> DrawMode {
+        DrawMode::Outlined {
+            fill_mode: FillMode::color(color),
+            outline_mode: StrokeMode::new(self.outline, self.outline_width * hit_points as f32),
+        }
+    }
+
+    /// Color of a brick of the given `kind` in the given `row` of a level design with `rows` rows.
+    pub fn brick_color(&self, row: u32, rows: u32, kind: BrickKind, rng: &mut impl Rng) -> Color {
+        if let (BrickKind::Strong, Some(color)) = (kind, self.strong_brick) {
+            return color;
+        }
+
+        match &self.bricks {
+            BrickPalette::Random(colors) => colors.choose(rng).copied(),
+            BrickPalette::Rows(colors) if !colors.is_empty() => {
+                let row_from_top = rows.saturating_sub(row + 1) as usize;
+                Some(colors[row_from_top % colors.len()])
+            }
+            BrickPalette::Rows(_) => None,
+        }
+        .unwrap_or(Color::WHITE)
+    }
+}
+
+/// Load [`Theme`] from files with `.theme.ron` extension.
+#[derive(Default)]
+pub struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let theme: Theme = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(theme));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+fn load_themes_system(
+    mut commands: Commands,
+    mut themes: ResMut<Assets<Theme>>,
+    asset_server: Res<AssetServer>,
+    cfg: Res<GameConfig>,
+) {
+    let handles: Vec<Handle<Theme>> = (0..BUILT_IN_THEMES.len())
+        .map(|index| themes.add(Theme::built_in(index)))
+        .chain(cfg.theme_paths.iter().map(|path| asset_server.load(path)))
+        .collect();
+
+    // The preferred theme may not exist anymore
+    let active = cfg.theme.min(handles.len() - 1);
+    commands.insert_resource(Themes { handles, active });
+}
+
+fn switch_theme_system(
+    keys: Res<Input<KeyCode>>,
+    mut themes: ResMut<Themes>,
+    theme_assets: Res<Assets<Theme>>,
+) {
+    if keys.just_pressed(KeyCode::T) {
+        // Skip the themes which are not loaded (yet)
+        for _ in 0..themes.handles.len() {
+            themes.active = (themes.active + 1) % themes.handles.len();
+            if theme_assets.contains(&themes.handles[themes.active]) {
+                break;
+            }
+        }
+    }
+}
+
+fn update_active_theme_system(
+    themes: Res<Themes>,
+    theme_assets: Res<Assets<Theme>>,
+    mut active_theme: ResMut<ActiveTheme>,
+    cfg: Res<GameConfig>,
+) {
+    if themes.is_changed() || cfg.is_changed() {
+        if let Some(theme) = theme_assets.get(&themes.handles[themes.active]) {
+            active_theme.0 = theme.accessible(&cfg);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_theme_system(
+    active_theme: Res<ActiveTheme>,
+    clear_color: Option<ResMut<ClearColor>>,
+    mut paddle_query: Query<&mut DrawMode, (With<Paddle>, Without<Ball>, Without<Brick>)>,
+    mut ball_query: Query<&mut DrawMode, (With<Ball>, Without<Brick>, Without<Wall>)>,
+    mut wall_query: Query<&mut DrawMode, (With<Wall>, Without<Paddle>, Without<Brick>)>,
+    mut brick_query: Query<
+        (
+            &mut DrawMode,
+            &mut BrickColor,
+            &BrickCell,
+            &BrickKind,
+            Option<&BrickHitPoints>,
+        ),
+        (With<Brick>, Without<Paddle>, Without<Wall>),
+    >,
+    custom_level: Res<CustomLevel>,
+    cfg: Res<GameConfig>,
+) {
+    if !active_theme.is_changed() {
+        return;
+    }
+    let theme = &active_theme.0;
+    let mut rng = rand::thread_rng();
+    // Generated levels fill the whole brick area
+    let rows = custom_level
+        .0
+        .as_ref()
+        .map_or(cfg.brick_area_rows, |design| design.rows);
+
+    if let Some(mut clear_color) = clear_color {
+        clear_color.0 = theme.background;
+    }
+
+    for mut draw_mode in paddle_query.iter_mut() {
+        *draw_mode = theme.outlined(theme.paddle);
+    }
+    for mut draw_mode in ball_query.iter_mut() {
+        *draw_mode = theme.outlined(theme.ball);
+    }
+    for mut draw_mode in wall_query.iter_mut() {
+        *draw_mode = DrawMode::Outlined {
+            fill_mode: FillMode::color(theme.wall),
+            outline_mode: StrokeMode::color(theme.wall),
+        };
+    }
+    for (mut draw_mode, mut brick_color, cell, &kind, hit_points) in brick_query.iter_mut() {
+        brick_color.0 = theme.brick_color(cell.row, rows, kind, &mut rng);
+        *draw_mode = theme.outlined_brick(brick_color.0, hit_points.map_or(1, |hp| hp.0));
+    }
+}
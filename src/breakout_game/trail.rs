@@ -0,0 +1,120 @@
+//! Ball trail related stuff.
+//!
+//! Every ball with a [`Trail`] leaves fading ghost circles at its recent positions,
+//! the trail gets longer and changes color as the ball goes faster.
+//!
+//!  - Spawn ghost circles for new trails at [`GameStage::Ui`] stage.
+//!  - Record ball positions and update ghost circles at [`GameStage::Ui`] stage.
+
+use super::{
+    components::{Trail, TrailGhost, Velocity},
+    resources::{ActiveTheme, GameConfig},
+    GameStage,
+};
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+/// Ball trail logic as a Bevy’s plugin.
+pub struct TrailPlugin;
+
+impl Plugin for TrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(GameStage::Ui, spawn_trail_ghost_system)
+            .add_system_to_stage(GameStage::Ui, update_trail_system);
+    }
+}
+
+/// Linear interpolation between two colors in RGBA space.
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let [r0, g0, b0, a0] = from.as_rgba_f32();
+    let [r1, g1, b1, a1] = to.as_rgba_f32();
+    Color::rgba(
+        r0 + (r1 - r0) * t,
+        g0 + (g1 - g0) * t,
+        b0 + (b1 - b0) * t,
+        a0 + (a1 - a0) * t,
+    )
+}
+
+fn spawn_trail_ghost_system(
+    mut commands: Commands,
+    trail_query: Query<Entity, Added<Trail>>,
+    theme: Res<ActiveTheme>,
+    cfg: Res<GameConfig>,
+) {
+    for ball in trail_query.iter() {
+        for index in 0..cfg.trail_max_length {
+            // Ghosts get smaller along the trail
+            let shape = shapes::Circle {
+                radius: cfg.ball_radius * (1. - index as f32 / cfg.trail_max_length as f32),
+                ..Default::default()
+            };
+
+            commands
+                .spawn(GeometryBuilder::build_as(
+                    &shape,
+                    DrawMode::Fill(FillMode::color(theme.0.trail_slow)),
+                    Transform::from_xyz(0., 0., cfg.trail_z),
+                ))
+                .insert(Visibility { is_visible: false })
+                .insert(TrailGhost { ball, index });
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_trail_system(
+    mut commands: Commands,
+    mut trail_query: Query<(&Transform, &Velocity, &mut Trail)>,
+    mut ghost_query: Query<
+        (
+            Entity,
+            &TrailGhost,
+            &mut Transform,
+            &mut DrawMode,
+            &mut Visibility,
+        ),
+        Without<Trail>,
+    >,
+    theme: Res<ActiveTheme>,
+    cfg: Res<GameConfig>,
+) {
+    for (tf, velocity, mut trail) in trail_query.iter_mut() {
+        if velocity.0 == Vec2::ZERO {
+            // Do not draw a trail between the lost position and the paddle
+            trail.positions.clear();
+        } else {
+            trail.positions.push_front(tf.translation.truncate());
+            trail.positions.truncate(cfg.trail_max_length);
+        }
+    }
+
+    for (entity, ghost, mut ghost_tf, mut draw_mode, mut visibility) in ghost_query.iter_mut() {
+        let (velocity, trail) = match trail_query.get(ghost.ball) {
+            Ok((_, velocity, trail)) => (velocity, trail),
+            Err(_) => {
+                // The ball does not exist anymore
+                commands.entity(entity).despawn();
+                continue;
+            }
+        };
+
+        let speed_ratio = (velocity.0.length() / cfg.trail_fast_speed).clamp(0., 1.);
+        let length = (speed_ratio * cfg.trail_max_length as f32).round() as usize;
+
+        match trail.positions.get(ghost.index) {
+            Some(position) if cfg.trail_enabled && ghost.index < length => {
+                ghost_tf.translation = position.extend(cfg.trail_z);
+                visibility.is_visible = true;
+
+                let mut color = lerp_color(theme.0.trail_slow, theme.0.trail_fast, speed_ratio);
+                let alpha = color.a() * (1. - ghost.index as f32 / length as f32);
+                color.set_a(alpha);
+                if let DrawMode::Fill(fill_mode) = draw_mode.as_mut() {
+                    fill_mode.color = color;
+                }
+            }
+            _ => visibility.is_visible = false,
+        }
+    }
+}
@@ -1,10 +1,11 @@
 //! Wall related stuff.
 //!
 //!  - Spawn walls once at [`StartupStage::PostStartup`] stage.
+//!    There is no top wall in [`GameMode::Versus`], the top of the window is the second player’s goal.
 
 use super::{
     components::{BallCollider, BoundingBox, Wall},
-    resources::GameConfig,
+    resources::{ActiveTheme, GameConfig, GameMode},
 };
 use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::*;
@@ -18,7 +19,12 @@ impl Plugin for WallPlugin {
     }
 }
 
-fn spawn_wall_system(mut commands: Commands, cfg: Res<GameConfig>) {
+fn spawn_wall_system(mut commands: Commands, theme: Res<ActiveTheme>, cfg: Res<GameConfig>) {
+    let draw_mode = DrawMode::Outlined {
+        fill_mode: FillMode::color(theme.0.wall),
+        outline_mode: StrokeMode::color(theme.0.wall),
+    };
+
     let mut spawn_vertical_wall = |x| {
         let shape = shapes::Rectangle {
             extents: Vec2 {
@@ -31,10 +37,7 @@ fn spawn_wall_system(mut commands: Commands, cfg: Res<GameConfig>) {
         commands
             .spawn(GeometryBuilder::build_as(
                 &shape,
-                DrawMode::Outlined {
-                    fill_mode: FillMode::color(Color::GRAY),
-                    outline_mode: StrokeMode::color(Color::GRAY),
-                },
+                draw_mode,
                 Transform {
                     translation: Vec3::new(x, 0., cfg.wall_z),
                     ..Default::default()
@@ -51,6 +54,10 @@ fn spawn_wall_system(mut commands: Commands, cfg: Res<GameConfig>) {
     spawn_vertical_wall(-cfg.window_width / 2.);
     spawn_vertical_wall(cfg.window_width / 2.);
 
+    if cfg.game_mode == GameMode::Versus {
+        return;
+    }
+
     let horizontal_wall_shape = shapes::Rectangle {
         extents: Vec2 {
             x: cfg.window_width,
@@ -62,10 +69,7 @@ fn spawn_wall_system(mut commands: Commands, cfg: Res<GameConfig>) {
     commands
         .spawn(GeometryBuilder::build_as(
             &horizontal_wall_shape,
-            DrawMode::Outlined {
-                fill_mode: FillMode::color(Color::GRAY),
-                outline_mode: StrokeMode::color(Color::GRAY),
-            },
+            draw_mode,
             Transform {
                 translation: Vec3::new(0., cfg.window_height / 2., cfg.wall_z),
                 ..Default::default()
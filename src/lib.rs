@@ -0,0 +1,9 @@
+//! A simple game of breakout to learn Bevy.
+//!
+//! The game is a library shared by the game’s binary and the benchmarks.
+
+#![warn(missing_docs)]
+
+pub mod breakout_game;
+
+pub use self::breakout_game::*;
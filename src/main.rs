@@ -6,30 +6,83 @@
 //#![allow(unused)]
 
 use auto_backend::AutoBackendPlugin;
-use bevy::prelude::*;
+use bevy::{prelude::*, window::WindowMode};
 use bevy_framepace::{FramepacePlugin, FramepaceSettings, Limiter};
 use bevy_prototype_lyon::prelude::*;
-use breakout_game::resources::GameConfig;
+use breakout_game::{
+    components::Player,
+    preferences::UserPreferences,
+    resources::{GameConfig, GameMode, Localization, NetConfig, TelemetryConfig},
+};
+use std::env;
 
 mod auto_backend;
-pub mod breakout_game;
 
 fn main() {
-    let cfg = GameConfig::default();
+    let mut cfg = GameConfig::default();
+    let mut preferences = UserPreferences::load();
+    if env::args().any(|arg| arg == "--editor") {
+        cfg.level_editor = true;
+    }
+    if env::args().any(|arg| arg == "--versus") {
+        cfg.game_mode = GameMode::Versus;
+    }
+    if env::args().any(|arg| arg == "--coop") {
+        cfg.game_mode = GameMode::Coop;
+    }
+    if env::args().any(|arg| arg == "--resume") {
+        cfg.resume_saved_game = true;
+    }
+    let args: Vec<String> = env::args().collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--net") {
+        let player = args.get(index + 3).and_then(|player| player.parse().ok());
+        match (args.get(index + 1), args.get(index + 2), player) {
+            (Some(local_addr), Some(peer_addr), Some(player)) => {
+                cfg.net = Some(NetConfig {
+                    local_addr: local_addr.clone(),
+                    peer_addr: peer_addr.clone(),
+                    local_player: Player(player),
+                    ..Default::default()
+                });
+                // Both peers must run the same simulation, the seed is chosen by the first player
+                preferences = preferences.with_simulation_of(&UserPreferences::default());
+                cfg.resume_saved_game = false;
+            }
+            _ => eprintln!("Usage: --net <local address> <peer address> <player 0 or 1>"),
+        }
+    }
+    preferences.apply(&mut cfg);
+    if let Some(index) = args.iter().position(|arg| arg == "--telemetry") {
+        match args.get(index + 1) {
+            Some(address) => {
+                cfg.telemetry = Some(TelemetryConfig {
+                    address: address.clone(),
+                    control: args.iter().any(|arg| arg == "--telemetry-control"),
+                })
+            }
+            None => eprintln!("Usage: --telemetry <address or unix:path> [--telemetry-control]"),
+        }
+    }
     let width = cfg.window_width;
     let height = cfg.window_height;
-    let background_color = cfg.background_color;
+    let mut localization = Localization::built_in();
+    localization.active = localization.find(&cfg.language).unwrap_or(0);
+    let mode = if cfg.fullscreen {
+        WindowMode::BorderlessFullscreen
+    } else {
+        WindowMode::Windowed
+    };
 
     App::new()
         .add_plugin(AutoBackendPlugin)
-        .insert_resource(ClearColor(background_color))
         .insert_resource(cfg)
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             window: WindowDescriptor {
                 width,
                 height,
-                title: "Breakout in Rust/Bevy".to_string(),
+                title: localization.text("window-title"),
                 resizable: false,
+                mode,
                 ..Default::default()
             },
             ..Default::default()
@@ -38,14 +91,17 @@ fn main() {
         .add_plugin(ShapePlugin)
         .add_plugins(breakout_game::DefaultPlugins)
         .add_startup_system(setup_system)
+        .add_system(framerate_limiter_system)
         .run();
 }
 
-fn setup_system(
-    mut commands: Commands,
-    mut settings: ResMut<FramepaceSettings>,
-    cfg: Res<GameConfig>,
-) {
-    settings.limiter = Limiter::from_framerate(cfg.framerate as f64);
+fn setup_system(mut commands: Commands) {
     commands.spawn(Camera2dBundle::default());
 }
+
+/// The frame rate cap can be changed in the settings.
+fn framerate_limiter_system(mut settings: ResMut<FramepaceSettings>, cfg: Res<GameConfig>) {
+    if cfg.is_changed() {
+        settings.limiter = Limiter::from_framerate(cfg.framerate as f64);
+    }
+}
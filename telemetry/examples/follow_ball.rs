@@ -0,0 +1,54 @@
+//! Out-of-process AI moving the first paddle under the nearest ball.
+//!
+//! Run the game with `--telemetry 127.0.0.1:7100 --telemetry-control`, then
+//! `cargo run -p breakout_telemetry --example follow_ball -- 127.0.0.1:7100`.
+
+use breakout_telemetry::{Client, Control, Message};
+use std::{env, io};
+
+/// Distance from the paddle’s center the ball may be, to not shake the paddle.
+const DEAD_ZONE: f32 = 8.;
+
+fn main() -> io::Result<()> {
+    let address = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:7100".to_string());
+    let mut client = Client::connect(&address)?;
+    let mut last_control = None;
+
+    while let Some(message) = client.read()? {
+        let snapshot = match message {
+            Message::Snapshot(snapshot) => snapshot,
+            _ => continue,
+        };
+        let paddle = match snapshot.paddles.iter().find(|paddle| paddle.player == 0) {
+            Some(paddle) => paddle,
+            None => continue,
+        };
+        let target = snapshot
+            .balls
+            .iter()
+            .min_by(|a, b| {
+                let distance = |y: f32| (y - paddle.position[1]).abs();
+                distance(a.position[1]).total_cmp(&distance(b.position[1]))
+            })
+            .map_or(0., |ball| ball.position[0]);
+
+        let offset = target - paddle.position[0];
+        let control = Control {
+            player: 0,
+            left: offset < -DEAD_ZONE,
+            right: offset > DEAD_ZONE,
+            // Throw the balls waiting on the paddle
+            launch: snapshot.balls.iter().any(|ball| ball.velocity == [0., 0.]),
+            ..Default::default()
+        };
+        // Only the changes are sent
+        if last_control != Some(control) {
+            client.send(&control)?;
+            last_control = Some(control);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,214 @@
+//! Client of the telemetry stream of the breakout game, for dashboards and external bots.
+//!
+//! The game publishes a [`Message`] per line in JSON, over TCP or a Unix socket:
+//! a [`Snapshot`] of the gameplay state each tick, after the gameplay events of the tick.
+//! When the game accepts control, a [`Control`] per line can be sent back to move the paddles.
+//!
+//! ```no_run
+//! use breakout_telemetry::{Client, Control, Message};
+//!
+//! let mut client = Client::connect("127.0.0.1:7100")?;
+//! while let Some(message) = client.read()? {
+//!     if let Message::Snapshot(snapshot) = message {
+//!         let (paddle, ball) = match (snapshot.paddles.first(), snapshot.balls.first()) {
+//!             (Some(paddle), Some(ball)) => (paddle, ball),
+//!             _ => continue,
+//!         };
+//!         client.send(&Control {
+//!             left: ball.position[0] < paddle.position[0],
+//!             right: ball.position[0] > paddle.position[0],
+//!             ..Default::default()
+//!         })?;
+//!     }
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+#![warn(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, BufRead, BufReader, ErrorKind, Read, Write},
+    net::TcpStream,
+    path::PathBuf,
+};
+
+/// Prefix of the addresses of Unix sockets, see [`Endpoint::parse`].
+pub const UNIX_PREFIX: &str = "unix:";
+
+/// A line of the telemetry stream.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    /// The gameplay state, once per tick.
+    Snapshot(Snapshot),
+    /// A ball destroyed a brick.
+    BrickDestroyed {
+        /// Position of the brick.
+        position: [f32; 2],
+        /// The player who hit the ball last, scoring the brick.
+        player: usize,
+    },
+    /// A ball went out of the playfield.
+    BallLost {
+        /// The player guarding the side where the ball went out.
+        player: usize,
+        /// Last position of the ball.
+        position: [f32; 2],
+    },
+    /// The score of a player changed.
+    ScoreChanged {
+        /// The player.
+        player: usize,
+        /// The new score.
+        score: i32,
+    },
+}
+
+/// Gameplay state of a tick, positions are in pixels from the center of the playfield.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Snapshot {
+    /// Number of the tick, since the server started.
+    pub tick: u64,
+    /// If true, the gameplay didn’t run during the tick.
+    pub paused: bool,
+    /// The paddles, by player.
+    pub paddles: Vec<BodySnapshot>,
+    /// The balls, the player is the one who hit the ball last.
+    pub balls: Vec<BodySnapshot>,
+    /// The remaining bricks.
+    pub bricks: Vec<BrickSnapshot>,
+    /// The scores, indexed by player.
+    pub scores: Vec<i32>,
+}
+
+/// A moving paddle or ball.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BodySnapshot {
+    /// Player owning the body.
+    pub player: usize,
+    /// Position of the center.
+    pub position: [f32; 2],
+    /// Velocity in pixels per second.
+    pub velocity: [f32; 2],
+}
+
+/// A remaining brick.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BrickSnapshot {
+    /// Column of the brick in the level.
+    pub col: u32,
+    /// Row of the brick in the level, from the bottom.
+    pub row: u32,
+    /// Position of the center.
+    pub position: [f32; 2],
+    /// Remaining hits to destroy the brick.
+    pub hit_points: u32,
+}
+
+/// Actions of a player’s paddle, held until the next control of the player.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Control {
+    /// The controlled player.
+    pub player: usize,
+    /// Move the paddle to the left.
+    pub left: bool,
+    /// Move the paddle to the right.
+    pub right: bool,
+    /// Throw a ball, only once.
+    pub launch: bool,
+    /// Aim to the left, before throwing the ball.
+    pub aim_left: bool,
+    /// Aim to the right, before throwing the ball.
+    pub aim_right: bool,
+}
+
+/// Address of the telemetry server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Endpoint {
+    /// TCP socket address, like `127.0.0.1:7100`.
+    Tcp(String),
+    /// Path of a Unix socket.
+    Unix(PathBuf),
+}
+
+impl Endpoint {
+    /// Parse `address`, a Unix socket’s path is prefixed by [`UNIX_PREFIX`], like `unix:/tmp/breakout.sock`.
+    pub fn parse(address: &str) -> Endpoint {
+        match address.strip_prefix(UNIX_PREFIX) {
+            Some(path) => Endpoint::Unix(PathBuf::from(path)),
+            None => Endpoint::Tcp(address.to_string()),
+        }
+    }
+}
+
+/// Connection to the telemetry server.
+pub struct Client {
+    /// Reading half of the connection.
+    reader: BufReader<Box<dyn Read + Send>>,
+    /// Writing half of the connection.
+    writer: Box<dyn Write + Send>,
+    /// Buffer of the line being read.
+    line: String,
+}
+
+impl Client {
+    /// Connect to the server at `address`, see [`Endpoint::parse`].
+    pub fn connect(address: &str) -> io::Result<Client> {
+        let (reader, writer): (Box<dyn Read + Send>, Box<dyn Write + Send>) =
+            match Endpoint::parse(address) {
+                Endpoint::Tcp(address) => {
+                    let stream = TcpStream::connect(address)?;
+                    stream.set_nodelay(true)?;
+                    (Box::new(stream.try_clone()?), Box::new(stream))
+                }
+                #[cfg(unix)]
+                Endpoint::Unix(path) => {
+                    let stream = std::os::unix::net::UnixStream::connect(path)?;
+                    (Box::new(stream.try_clone()?), Box::new(stream))
+                }
+                #[cfg(not(unix))]
+                Endpoint::Unix(_) => {
+                    return Err(io::Error::new(
+                        ErrorKind::Unsupported,
+                        "Unix sockets are not supported",
+                    ))
+                }
+            };
+
+        Ok(Client {
+            reader: BufReader::new(reader),
+            writer,
+            line: String::new(),
+        })
+    }
+
+    /// Wait for the next message, `None` once the server closed the connection.
+    pub fn read(&mut self) -> io::Result<Option<Message>> {
+        self.line.clear();
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Ok(None);
+        }
+
+        serde_json::from_str(&self.line)
+            .map(Some)
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+    }
+
+    /// Send `control`, ignored if the server doesn’t accept control.
+    pub fn send(&mut self, control: &Control) -> io::Result<()> {
+        let mut line = serde_json::to_vec(control)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)?;
+        self.writer.flush()
+    }
+}
+
+impl Iterator for Client {
+    type Item = io::Result<Message>;
+
+    fn next(&mut self) -> Option<io::Result<Message>> {
+        self.read().transpose()
+    }
+}
@@ -0,0 +1,120 @@
+//! Two headless peers in lockstep over loopback sockets, with a simulated lossy and latent link.
+
+use bevy::{asset::AssetPlugin, input::InputPlugin, prelude::*};
+use breakout_game::{
+    components::Player,
+    net::NetSession,
+    resources::{GameConfig, GameMode, GameRng, LinkConditions},
+    HeadlessPlugins,
+};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Versus game, balls are thrown quickly.
+fn config(seed: u64) -> GameConfig {
+    GameConfig {
+        game_mode: GameMode::Versus,
+        seed: Some(seed),
+        ball_auto_launch_delay: Some(0.5),
+        ..Default::default()
+    }
+}
+
+/// Link losing a packet out of five, with latency and jitter.
+fn lossy_link() -> LinkConditions {
+    LinkConditions {
+        loss: 0.2,
+        latency: Duration::from_millis(20),
+        jitter: Duration::from_millis(10),
+    }
+}
+
+/// Two connected headless peers with the seeds `seeds`.
+fn peers(seeds: [u64; 2], link: LinkConditions) -> [App; 2] {
+    let mut sessions = [0, 1].map(|player| {
+        NetSession::bind("127.0.0.1:0", Player(player), 3, link).expect("loopback socket")
+    });
+    let addrs = [0, 1].map(|index| sessions[index].local_addr().unwrap());
+    sessions[0].connect(addrs[1]);
+    sessions[1].connect(addrs[0]);
+
+    let mut index = 0;
+    sessions.map(|session| {
+        let mut app = App::new();
+        app.insert_resource(config(seeds[index]))
+            .insert_resource(session)
+            .add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_plugin(InputPlugin)
+            .add_plugins(HeadlessPlugins);
+        index += 1;
+        app
+    })
+}
+
+/// Update both peers until both have simulated `ticks` ticks or `done` returns true.
+fn run(apps: &mut [App; 2], ticks: u32, done: impl Fn(&NetSession) -> bool) {
+    let start = Instant::now();
+    while apps.iter().any(|app| {
+        let session = app.world.resource::<NetSession>();
+        session.tick() < ticks && !done(session)
+    }) {
+        assert!(
+            start.elapsed() < Duration::from_secs(30),
+            "peers stuck at ticks {:?}",
+            apps.iter()
+                .map(|app| app.world.resource::<NetSession>().tick())
+                .collect::<Vec<_>>()
+        );
+        for app in apps.iter_mut() {
+            app.update();
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn peers_stay_in_lockstep_over_a_lossy_link() {
+    let mut apps = peers([42, 42], lossy_link());
+    run(&mut apps, 600, |_| false);
+
+    let first = apps[0].world.resource::<NetSession>();
+    let second = apps[1].world.resource::<NetSession>();
+    assert!(first.desync().is_none());
+    assert!(second.desync().is_none());
+    for tick in 500..600 {
+        assert_eq!(first.hash(tick), second.hash(tick), "tick {}", tick);
+        assert!(first.hash(tick).is_some());
+    }
+}
+
+#[test]
+fn peers_play_the_seed_of_the_first_player() {
+    let mut apps = peers([1, 2], LinkConditions::default());
+    run(&mut apps, 100, |_| false);
+
+    for app in apps.iter() {
+        let session = app.world.resource::<NetSession>();
+        assert_eq!(session.seed(), Some(1));
+        assert!(session.desync().is_none());
+    }
+}
+
+#[test]
+fn different_simulations_are_detected() {
+    let mut apps = peers([42, 42], LinkConditions::default());
+    run(&mut apps, 100, |_| false);
+
+    // The second peer draws different numbers from now on
+    let mut rng = apps[1].world.resource_mut::<GameRng>();
+    let word_pos = rng.0.get_word_pos();
+    rng.0.set_word_pos(word_pos + 1);
+    run(&mut apps, 600, |session| session.desync().is_some());
+
+    for app in apps.iter() {
+        let desync = app.world.resource::<NetSession>().desync();
+        assert!(desync.is_some());
+    }
+}
@@ -0,0 +1,67 @@
+//! Headless round trip of a saved game: the resumed game must run exactly like the original one.
+
+use bevy::{asset::AssetPlugin, input::InputPlugin, prelude::*, time::TimeUpdateStrategy};
+use breakout_game::{resources::GameConfig, save::SavedGame, HeadlessPlugins};
+use std::time::{Duration, Instant};
+
+/// Seeded configuration, balls are thrown quickly.
+fn config() -> GameConfig {
+    GameConfig {
+        seed: Some(42),
+        ball_auto_launch_delay: Some(0.5),
+        ..Default::default()
+    }
+}
+
+/// Headless game advanced by a fixed time step, after its first frame.
+fn headless_app(start: Instant) -> App {
+    let mut app = App::new();
+    app.insert_resource(config())
+        .insert_resource(TimeUpdateStrategy::ManualInstant(start))
+        .add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin::default())
+        .add_plugin(InputPlugin)
+        .add_plugins(HeadlessPlugins);
+    app.update();
+    app
+}
+
+/// Run `frames` frames of a fixed duration.
+fn run(app: &mut App, frames: u32) {
+    let frame = Duration::from_secs_f32(app.world.resource::<GameConfig>().time_step());
+    for _ in 0..frames {
+        if let TimeUpdateStrategy::ManualInstant(instant) =
+            app.world.resource_mut::<TimeUpdateStrategy>().as_mut()
+        {
+            *instant += frame;
+        }
+        app.update();
+    }
+}
+
+/// Text of the gameplay state, to compare two games.
+fn snapshot(app: &mut App) -> String {
+    ron::to_string(&SavedGame::capture(&mut app.world)).unwrap()
+}
+
+#[test]
+fn resumed_game_runs_identically() {
+    let start = Instant::now();
+
+    let mut original = headless_app(start);
+    run(&mut original, 300);
+    let saved_game = SavedGame::capture(&mut original.world);
+
+    // Through the file format too
+    let text = ron::to_string(&saved_game).unwrap();
+    let saved_game: SavedGame = ron::from_str(&text).unwrap();
+
+    let mut resumed = headless_app(start);
+    saved_game.restore(&mut resumed.world);
+    assert_eq!(snapshot(&mut original), snapshot(&mut resumed));
+
+    // After bounces, lost balls and destroyed bricks
+    run(&mut original, 600);
+    run(&mut resumed, 600);
+    assert_eq!(snapshot(&mut original), snapshot(&mut resumed));
+}
@@ -0,0 +1,107 @@
+//! A telemetry client of a headless game, reading its snapshots and moving its paddle.
+
+use bevy::{asset::AssetPlugin, input::InputPlugin, prelude::*};
+use breakout_game::{resources::GameConfig, telemetry::TelemetryServer, HeadlessPlugins};
+use breakout_telemetry::{Client, Control, Message, Snapshot};
+use std::{thread, time::Duration};
+
+/// Headless game publishing on a loopback socket, and its connected client.
+fn connected_app() -> (App, Client) {
+    let server = TelemetryServer::bind("127.0.0.1:0", true).expect("loopback socket");
+    let address = server.local_addr().unwrap().to_string();
+
+    let mut app = App::new();
+    app.insert_resource(GameConfig {
+        seed: Some(42),
+        ..Default::default()
+    })
+    .insert_resource(server)
+    .add_plugins(MinimalPlugins)
+    .add_plugin(AssetPlugin::default())
+    .add_plugin(InputPlugin)
+    .add_plugins(HeadlessPlugins);
+
+    let client = Client::connect(&address).expect("connection to the game");
+    (app, client)
+}
+
+/// Run a frame and read its snapshot, the events of the frame are sent before it.
+fn next_snapshot(app: &mut App, client: &mut Client) -> Snapshot {
+    app.update();
+    loop {
+        match client.read().unwrap() {
+            Some(Message::Snapshot(snapshot)) => return snapshot,
+            Some(_) => continue,
+            None => panic!("connection closed by the game"),
+        }
+    }
+}
+
+#[test]
+fn clients_read_the_state_and_move_the_paddles() {
+    let (mut app, mut client) = connected_app();
+
+    let snapshot = next_snapshot(&mut app, &mut client);
+    assert!(!snapshot.bricks.is_empty());
+    assert_eq!(snapshot.paddles.len(), 1);
+    assert_eq!(snapshot.balls.len(), 1);
+    assert_eq!(snapshot.scores, vec![0]);
+    let start = snapshot.paddles[0].position;
+
+    client
+        .send(&Control {
+            player: 0,
+            left: true,
+            ..Default::default()
+        })
+        .unwrap();
+    let mut moved = false;
+    for _ in 0..100 {
+        let snapshot = next_snapshot(&mut app, &mut client);
+        if snapshot.paddles[0].position[0] < start[0] {
+            moved = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert!(moved, "the paddle didn’t move to the left");
+}
+
+#[cfg(unix)]
+#[test]
+fn only_stale_unix_sockets_are_replaced() {
+    use std::{fs, io::ErrorKind, os::unix::net::UnixListener};
+
+    let dir = std::env::temp_dir().join(format!("breakout-telemetry-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("telemetry.sock");
+    let address = format!("unix:{}", path.display());
+    let bind_error = || {
+        TelemetryServer::bind(&address, false)
+            .err()
+            .map(|err| err.kind())
+    };
+
+    // Another file is kept
+    fs::write(&path, "data").unwrap();
+    assert_eq!(bind_error(), Some(ErrorKind::AddrInUse));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "data");
+    fs::remove_file(&path).unwrap();
+
+    // A socket still listened on is kept
+    let listener = UnixListener::bind(&path).unwrap();
+    assert_eq!(bind_error(), Some(ErrorKind::AddrInUse));
+
+    // A stale socket is replaced
+    drop(listener);
+    let server = TelemetryServer::bind(&address, false).expect("stale socket replaced");
+
+    // The socket of another server isn’t removed with the first server
+    fs::remove_file(&path).unwrap();
+    let listener = UnixListener::bind(&path).unwrap();
+    drop(server);
+    assert!(path.exists());
+
+    drop(listener);
+    fs::remove_dir_all(&dir).unwrap();
+}